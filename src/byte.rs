@@ -0,0 +1,719 @@
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+use primitives::{Consumed, Info, Parser, ParseError, ParseResult, Error, Resumable, State, Stream};
+use combinator::{Expected, skip_many, SkipMany, ParserExt, With};
+
+macro_rules! impl_byte_parser {
+    ($name: ident ($($ty_var: ident),*), $inner_type: ty) => {
+    #[derive(Clone)]
+    pub struct $name<I $(,$ty_var)*>($inner_type, PhantomData<fn (I) -> I>)
+        where I: Stream, I::Item: Borrow<u8> $(, $ty_var : Parser<Input=I>)*;
+    impl <I $(,$ty_var)*> Parser for $name<I $(,$ty_var)*>
+        where I: Stream, I::Item: Borrow<u8> $(, $ty_var : Parser<Input=I>)* {
+        type Input = I;
+        type Output = <$inner_type as Parser>::Output;
+        fn parse_lazy(&mut self, input: State<<Self as Parser>::Input>) -> ParseResult<<Self as Parser>::Output, <Self as Parser>::Input> {
+            self.0.parse_lazy(input)
+        }
+        fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+            self.0.add_error(errors)
+        }
+    }
+    impl <I $(,$ty_var)*> Resumable for $name<I $(,$ty_var)*>
+        where I: Stream, I::Item: Borrow<u8> $(, $ty_var : Parser<Input=I>)*
+            , $inner_type: Resumable<Input=I> {
+        type PartialState = <$inner_type as Resumable>::PartialState;
+        fn parse_resume(&mut self, input: State<I>, state: &mut Self::PartialState) -> ParseResult<<Self as Parser>::Output, I> {
+            self.0.parse_resume(input, state)
+        }
+    }
+}
+}
+
+///Parses a byte and succeeds if `predicate` returns true for it.
+///The byte is returned by value for streams of both `u8` and `&u8`.
+#[derive(Clone)]
+pub struct ByteSatisfy<I, P>(P, PhantomData<fn (I) -> I>);
+impl <I, P> Parser for ByteSatisfy<I, P>
+    where I: Stream
+        , I::Item: Borrow<u8>
+        , P: FnMut(u8) -> bool {
+    type Input = I;
+    type Output = u8;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<u8, I> {
+        match input.input.clone().uncons() {
+            Ok((b, rest)) => {
+                let byte = *b.borrow();
+                if (self.0)(byte) {
+                    let (_, rest) = try!(input.update(b, rest));
+                    Ok((byte, rest))
+                }
+                else {
+                    Err(Consumed::Empty(ParseError::empty(input.position)))
+                }
+            }
+            Err(err) => Err(input.uncons_error(err))
+        }
+    }
+}
+
+fn byte_satisfy<I>(predicate: fn (u8) -> bool) -> ByteSatisfy<I, fn (u8) -> bool>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    ByteSatisfy(predicate, PhantomData)
+}
+
+#[derive(Clone)]
+pub struct Byte<I>(u8, PhantomData<fn (I) -> I>);
+impl <I> Parser for Byte<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    type Input = I;
+    type Output = u8;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<u8, I> {
+        let c = self.0;
+        ByteSatisfy(|b| b == c, PhantomData).parse_lazy(input)
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected(byte_info(self.0)));
+    }
+}
+
+///Parses a byte and succeeds if the byte is equal to `c`
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::byte::byte;
+/// # fn main() {
+/// let result = byte(b'!')
+///     .parse(&b"!"[..])
+///     .map(|x| x.0);
+/// assert_eq!(result, Ok(b'!'));
+/// # }
+/// ```
+pub fn byte<I>(c: u8) -> Byte<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Byte(c, PhantomData)
+}
+
+impl_byte_parser! { Digit(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses an ASCII digit
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::byte::digit;
+/// # use pc::primitives::from_slice;
+/// # fn main() {
+/// assert_eq!(digit().parse(&b"9"[..]).map(|x| x.0), Ok(b'9'));
+/// assert_eq!(digit().parse(from_slice(&b"9"[..])).map(|x| x.0), Ok(b'9'));
+/// assert!(digit().parse(&b"A"[..]).is_err());
+/// # }
+/// ```
+pub fn digit<I>() -> Digit<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Digit(byte_satisfy(static_fn!((c, u8) -> bool { c.is_ascii_digit() }))
+         .expected("digit"), PhantomData)
+}
+
+impl_byte_parser! { Space(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses an ASCII whitespace byte
+pub fn space<I>() -> Space<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Space(byte_satisfy(static_fn!((c, u8) -> bool { c.is_ascii_whitespace() }))
+         .expected("whitespace"), PhantomData)
+}
+
+impl_byte_parser! { Spaces(), Expected<SkipMany<Space<I>>> }
+///Skips over zero or more ASCII whitespace bytes
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::byte::spaces;
+/// # fn main() {
+/// assert_eq!(spaces().parse(&b" \t\r\nabc"[..]), Ok(((), &b"abc"[..])));
+/// # }
+/// ```
+pub fn spaces<I>() -> Spaces<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Spaces(skip_many(space())
+          .expected("whitespaces"), PhantomData)
+}
+
+impl_byte_parser! { NewLine(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses a newline byte
+pub fn newline<I>() -> NewLine<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    NewLine(byte_satisfy(static_fn!((b, u8) -> bool { b == b'\n' }))
+           .expected("lf newline"), PhantomData)
+}
+
+impl_byte_parser! { CrLf(), Expected<With<ByteSatisfy<I, fn (u8) -> bool>, NewLine<I>>> }
+///Parses carriage return and newline, returning the newline byte.
+pub fn crlf<I>() -> CrLf<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    CrLf(byte_satisfy(static_fn!((b, u8) -> bool { b == b'\r' }))
+        .with(newline())
+        .expected("crlf newline"), PhantomData)
+}
+
+impl_byte_parser! { Tab(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses a tab byte
+pub fn tab<I>() -> Tab<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Tab(byte_satisfy(static_fn!((b, u8) -> bool { b == b'\t' }))
+       .expected("tab"), PhantomData)
+}
+
+impl_byte_parser! { Upper(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses an ASCII uppercase letter
+pub fn upper<I>() -> Upper<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Upper(byte_satisfy(static_fn!((b, u8) -> bool { b.is_ascii_uppercase() }))
+         .expected("uppercase letter"), PhantomData)
+}
+
+impl_byte_parser! { Lower(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses an ASCII lowercase letter
+pub fn lower<I>() -> Lower<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Lower(byte_satisfy(static_fn!((b, u8) -> bool { b.is_ascii_lowercase() }))
+         .expected("lowercase letter"), PhantomData)
+}
+
+impl_byte_parser! { AlphaNum(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses either an ASCII letter or digit
+pub fn alpha_num<I>() -> AlphaNum<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    AlphaNum(byte_satisfy(static_fn!((b, u8) -> bool { b.is_ascii_alphanumeric() }))
+            .expected("letter or digit"), PhantomData)
+}
+
+impl_byte_parser! { Letter(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses an ASCII letter
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::byte::letter;
+/// # fn main() {
+/// assert_eq!(many(letter()).parse(&b"abc123"[..]), Ok((b"abc".to_vec(), &b"123"[..])));
+/// # }
+/// ```
+pub fn letter<I>() -> Letter<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    Letter(byte_satisfy(static_fn!((b, u8) -> bool { b.is_ascii_alphabetic() }))
+          .expected("letter"), PhantomData)
+}
+
+impl_byte_parser! { OctDigit(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses an octal digit
+pub fn oct_digit<I>() -> OctDigit<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    OctDigit(byte_satisfy(static_fn!((b, u8) -> bool { b'0' <= b && b <= b'7' }))
+            .expected("octal digit"), PhantomData)
+}
+
+impl_byte_parser! { HexDigit(), Expected<ByteSatisfy<I, fn (u8) -> bool>> }
+///Parses a hexdecimal digit with uppercase and lowercase
+pub fn hex_digit<I>() -> HexDigit<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    HexDigit(byte_satisfy(static_fn!((b, u8) -> bool { b.is_ascii_hexdigit() }))
+            .expected("hexadecimal digit"), PhantomData)
+}
+
+fn byte_info<T, R>(b: u8) -> Info<T, R> {
+    if b.is_ascii() && !b.is_ascii_control() {
+        Info::Owned((b as char).to_string())
+    }
+    else {
+        Info::Owned(format!("{:?}", b))
+    }
+}
+
+fn bytes_info<T, R>(s: &'static [u8]) -> Info<T, R> {
+    match ::std::str::from_utf8(s) {
+        Ok(s) => Info::Borrowed(s),
+        Err(_) => Info::Owned(format!("{:?}", s))
+    }
+}
+
+#[derive(Clone)]
+pub struct BytesCmp<C, I>(&'static [u8], C, PhantomData<fn (I) -> I>);
+impl <C, I> Parser for BytesCmp<C, I>
+    where C: FnMut(u8, u8) -> bool
+        , I: Stream
+        , I::Item: Borrow<u8> {
+    type Input = I;
+    type Output = &'static [u8];
+    fn parse_lazy(&mut self, mut input: State<I>) -> ParseResult<&'static [u8], I> {
+        let start = input.position.clone();
+        let mut consumed = false;
+        for &b in self.0 {
+            match input.uncons() {
+                Ok((other, rest)) => {
+                    if !(self.1)(b, *other.borrow()) {
+                        return Err(if consumed {
+                            let errors = vec![
+                                Error::Unexpected(Info::Token(other)),
+                                Error::Expected(bytes_info(self.0))
+                            ];
+                            let error = ParseError::from_errors(start, errors);
+                            Consumed::Consumed(error)
+                        } else {
+                            Consumed::Empty(ParseError::empty(start))
+                        })
+                    }
+                    consumed = true;
+                    input = rest.into_inner();
+                }
+                Err(error) => {
+                    let error = error.map(|mut error| {
+                        error.position = start;
+                        error
+                    });
+                    return Err(if consumed { error.as_consumed() } else { error })
+                }
+            }
+        }
+        Ok((self.0, if consumed { Consumed::Consumed(input) } else { Consumed::Empty(input) }))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected(bytes_info(self.0)));
+    }
+}
+
+///Parses the bytes `s`, using `cmp` to compare each byte.
+///`cmp` is called with the expected byte as its first argument and the byte from the stream as
+///its second argument.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::byte::bytes_cmp;
+/// # use pc::primitives::from_slice;
+/// # fn main() {
+/// let mut method = bytes_cmp(b"GET", |l: u8, r: u8| l.eq_ignore_ascii_case(&r));
+/// assert_eq!(method.parse(from_slice(&b"get /"[..])), Ok((&b"GET"[..], from_slice(&b" /"[..]))));
+/// assert!(method.parse(from_slice(&b"PUT /"[..])).is_err());
+/// # }
+/// ```
+pub fn bytes_cmp<C, I>(s: &'static [u8], cmp: C) -> BytesCmp<C, I>
+    where C: FnMut(u8, u8) -> bool
+        , I: Stream
+        , I::Item: Borrow<u8> {
+    BytesCmp(s, cmp, PhantomData)
+}
+
+impl_byte_parser! { Bytes(), BytesCmp<fn (u8, u8) -> bool, I> }
+///Parses the bytes `s`
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::byte::bytes;
+/// # use pc::primitives::from_slice;
+/// # fn main() {
+/// let result = bytes(b"rust")
+///     .parse(from_slice(&b"rust"[..]))
+///     .map(|x| x.0);
+/// assert_eq!(result, Ok(&b"rust"[..]));
+/// # }
+/// ```
+pub fn bytes<I>(s: &'static [u8]) -> Bytes<I>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    fn eq(l: u8, r: u8) -> bool { l == r }
+    let eq: fn (u8, u8) -> bool = eq;
+    Bytes(bytes_cmp(s, eq), PhantomData)
+}
+
+#[derive(Clone)]
+pub struct Number<I, T> {
+    size: usize,
+    name: &'static str,
+    convert: fn (&[u8]) -> T,
+    _marker: PhantomData<fn (I) -> I>
+}
+
+impl <I, T> Number<I, T> {
+    fn expected_info<U, R>(&self) -> Info<U, R> {
+        Info::Owned(format!("{} bytes for {}", self.size, self.name))
+    }
+}
+
+impl <I, T> Parser for Number<I, T>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    type Input = I;
+    type Output = T;
+    fn parse_lazy(&mut self, mut input: State<I>) -> ParseResult<T, I> {
+        let start = input.position.clone();
+        let mut buffer = [0; 8];
+        for i in 0..self.size {
+            match input.uncons() {
+                Ok((b, rest)) => {
+                    buffer[i] = *b.borrow();
+                    input = rest.into_inner();
+                }
+                Err(err) => {
+                    let err = err.map(|mut err| {
+                        err.position = start;
+                        err.add_error(Error::Expected(self.expected_info()));
+                        err
+                    });
+                    return Err(if i == 0 { err } else { err.as_consumed() })
+                }
+            }
+        }
+        Ok(((self.convert)(&buffer[..self.size]), Consumed::Consumed(input)))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected(self.expected_info()));
+    }
+}
+
+fn from_be(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64)
+}
+
+fn from_le(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u64)
+}
+
+macro_rules! number_parser {
+    ($(#[$attr: meta])* $name: ident, $ty: ident, $size: expr, $bytes: ident => $convert: expr) => {
+        $(#[$attr])*
+        pub fn $name<I>() -> Number<I, $ty>
+            where I: Stream
+                , I::Item: Borrow<u8> {
+            fn convert($bytes: &[u8]) -> $ty { $convert }
+            Number { size: $size, name: stringify!($ty), convert: convert, _marker: PhantomData }
+        }
+    }
+}
+
+number_parser! {
+    ///Parses a big endian `u16`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::byte::be_u16;
+    /// # use pc::primitives::from_slice;
+    /// # fn main() {
+    /// assert_eq!(be_u16().parse(from_slice(&b"\x01\x02"[..])).map(|x| x.0), Ok(0x0102));
+    /// # }
+    /// ```
+    be_u16, u16, 2, bytes => from_be(bytes) as u16
+}
+number_parser! {
+    ///Parses a big endian `u32`
+    be_u32, u32, 4, bytes => from_be(bytes) as u32
+}
+number_parser! {
+    ///Parses a big endian `u64`
+    be_u64, u64, 8, bytes => from_be(bytes)
+}
+number_parser! {
+    ///Parses a big endian `i16`
+    be_i16, i16, 2, bytes => from_be(bytes) as u16 as i16
+}
+number_parser! {
+    ///Parses a big endian `i32`
+    be_i32, i32, 4, bytes => from_be(bytes) as u32 as i32
+}
+number_parser! {
+    ///Parses a big endian `i64`
+    be_i64, i64, 8, bytes => from_be(bytes) as i64
+}
+number_parser! {
+    ///Parses a big endian `f32`
+    be_f32, f32, 4, bytes => f32::from_bits(from_be(bytes) as u32)
+}
+number_parser! {
+    ///Parses a big endian `f64`
+    be_f64, f64, 8, bytes => f64::from_bits(from_be(bytes))
+}
+number_parser! {
+    ///Parses a little endian `u16`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::byte::le_u16;
+    /// # use pc::primitives::from_slice;
+    /// # fn main() {
+    /// assert_eq!(le_u16().parse(from_slice(&b"\x01\x02"[..])).map(|x| x.0), Ok(0x0201));
+    /// # }
+    /// ```
+    le_u16, u16, 2, bytes => from_le(bytes) as u16
+}
+number_parser! {
+    ///Parses a little endian `u32`
+    le_u32, u32, 4, bytes => from_le(bytes) as u32
+}
+number_parser! {
+    ///Parses a little endian `u64`
+    le_u64, u64, 8, bytes => from_le(bytes)
+}
+number_parser! {
+    ///Parses a little endian `i16`
+    le_i16, i16, 2, bytes => from_le(bytes) as u16 as i16
+}
+number_parser! {
+    ///Parses a little endian `i32`
+    le_i32, i32, 4, bytes => from_le(bytes) as u32 as i32
+}
+number_parser! {
+    ///Parses a little endian `i64`
+    le_i64, i64, 8, bytes => from_le(bytes) as i64
+}
+number_parser! {
+    ///Parses a little endian `f32`
+    le_f32, f32, 4, bytes => f32::from_bits(from_le(bytes) as u32)
+}
+number_parser! {
+    ///Parses a little endian `f64`
+    le_f64, f64, 8, bytes => f64::from_bits(from_le(bytes))
+}
+
+#[derive(Clone)]
+pub struct Leb128<I, T> {
+    bits: u32,
+    signed: bool,
+    name: &'static str,
+    convert: fn (i128) -> T,
+    _marker: PhantomData<fn (I) -> I>
+}
+
+impl <I, T> Leb128<I, T> {
+    fn expected_info<U, R>(&self) -> Info<U, R> {
+        Info::Owned(format!("LEB128 encoded {}", self.name))
+    }
+}
+
+impl <I, T> Parser for Leb128<I, T>
+    where I: Stream
+        , I::Item: Borrow<u8> {
+    type Input = I;
+    type Output = T;
+    fn parse_lazy(&mut self, mut input: State<I>) -> ParseResult<T, I> {
+        let start = input.position.clone();
+        let max_bytes = (self.bits + 6) / 7;
+        let mut value: i128 = 0;
+        let mut shift = 0;
+        let mut last = 0;
+        while last & 0x80 != 0 || shift == 0 {
+            if shift / 7 == max_bytes {
+                let error = Error::Message(format!("LEB128 value overflows {}", self.name).into());
+                return Err(Consumed::Consumed(ParseError::new(start, error)))
+            }
+            match input.uncons() {
+                Ok((b, rest)) => {
+                    let b = *b.borrow();
+                    value |= ((b & 0x7f) as i128) << shift;
+                    shift += 7;
+                    last = b;
+                    input = rest.into_inner();
+                }
+                Err(err) => {
+                    let err = err.map(|mut err| {
+                        err.position = start;
+                        err.add_error(Error::Expected(self.expected_info()));
+                        err
+                    });
+                    return Err(if shift == 0 { err } else { err.as_consumed() })
+                }
+            }
+        }
+        let (min, max) = if self.signed {
+            if last & 0x40 != 0 {
+                //Sign extend the value
+                value |= -1i128 << shift;
+            }
+            (-1i128 << (self.bits - 1), (1i128 << (self.bits - 1)) - 1)
+        }
+        else {
+            (0, (1i128 << self.bits) - 1)
+        };
+        if value < min || value > max {
+            let error = Error::Message(format!("LEB128 value overflows {}", self.name).into());
+            return Err(Consumed::Consumed(ParseError::new(start, error)))
+        }
+        Ok(((self.convert)(value), Consumed::Consumed(input)))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected(self.expected_info()));
+    }
+}
+
+macro_rules! leb128_parser {
+    ($(#[$attr: meta])* $name: ident, $ty: ident, $signed: expr) => {
+        $(#[$attr])*
+        pub fn $name<I>() -> Leb128<I, $ty>
+            where I: Stream
+                , I::Item: Borrow<u8> {
+            fn convert(value: i128) -> $ty { value as $ty }
+            Leb128 {
+                bits: ::std::mem::size_of::<$ty>() as u32 * 8,
+                signed: $signed,
+                name: stringify!($ty),
+                convert: convert,
+                _marker: PhantomData
+            }
+        }
+    }
+}
+
+leb128_parser! {
+    ///Parses an unsigned LEB128 encoded `u32`, failing if the value does not fit in a `u32`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::byte::leb128_u32;
+    /// # use pc::primitives::from_slice;
+    /// # fn main() {
+    /// assert_eq!(leb128_u32().parse(from_slice(&b"\xE5\x8E\x26"[..])).map(|x| x.0), Ok(624485));
+    /// assert!(leb128_u32().parse(from_slice(&b"\xFF\xFF\xFF\xFF\x7F"[..])).is_err());
+    /// # }
+    /// ```
+    leb128_u32, u32, false
+}
+leb128_parser! {
+    ///Parses an unsigned LEB128 encoded `u64`, failing if the value does not fit in a `u64`
+    leb128_u64, u64, false
+}
+leb128_parser! {
+    ///Parses a signed LEB128 encoded `i32`, failing if the value does not fit in an `i32`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::byte::leb128_i32;
+    /// # use pc::primitives::from_slice;
+    /// # fn main() {
+    /// assert_eq!(leb128_i32().parse(from_slice(&b"\xC0\xBB\x78"[..])).map(|x| x.0), Ok(-123456));
+    /// # }
+    /// ```
+    leb128_i32, i32, true
+}
+leb128_parser! {
+    ///Parses a signed LEB128 encoded `i64`, failing if the value does not fit in an `i64`
+    leb128_i64, i64, true
+}
+
+impl_restart!(ByteSatisfy<I, P>, Byte<I>, BytesCmp<C, I>, Number<I, T>, Leb128<I, T>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::{BytePosition, Error, ParseError, Parser, from_slice};
+    use combinator::{many1, ParserExt};
+
+    #[test]
+    fn space_error() {
+        let result = space()
+            .parse(from_slice(&b""[..]));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().errors, vec![Error::Message("End of input".into()), Error::Expected("whitespace".into())]);
+    }
+
+    #[test]
+    fn bytes_consumed() {
+        let result = bytes(b"a").parse(from_slice(&b"b"[..]));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().position, BytePosition { position: 0 });
+    }
+
+    #[test]
+    fn bytes_error() {
+        let result = bytes(b"abc").parse(from_slice(&b"abd"[..]));
+        assert_eq!(result, Err(ParseError {
+            position: BytePosition { position: 0 },
+            errors: vec![Error::Unexpected(b'd'.into()), Error::Expected("abc".into())]
+        }));
+    }
+
+    #[test]
+    fn numbers() {
+        let input = from_slice(&b"\x01\x02\x03\x04\x05\x06\x07\x08"[..]);
+        assert_eq!(be_u32().parse(input).map(|x| x.0), Ok(0x01020304));
+        assert_eq!(le_u32().parse(input).map(|x| x.0), Ok(0x04030201));
+        assert_eq!(be_u64().parse(input).map(|x| x.0), Ok(0x0102030405060708));
+        assert_eq!(le_i16().parse(from_slice(&b"\xFE\xFF"[..])).map(|x| x.0), Ok(-2));
+        assert_eq!(be_i64().parse(from_slice(&[0xFF; 8][..])).map(|x| x.0), Ok(-1));
+        assert_eq!(be_f32().parse(from_slice(&b"\x3F\x80\x00\x00"[..])).map(|x| x.0), Ok(1.0));
+        assert_eq!(le_f64().parse(from_slice(&b"\x00\x00\x00\x00\x00\x00\xF0\x3F"[..])).map(|x| x.0), Ok(1.0));
+        assert_eq!((be_u16(), le_u16()).parse(input).map(|x| x.0), Ok((0x0102, 0x0403)));
+    }
+
+    #[test]
+    fn number_error() {
+        let result = (byte(b'a'), be_u32()).parse(from_slice(&b"a\x01\x02"[..]));
+        assert_eq!(result, Err(ParseError {
+            position: BytePosition { position: 1 },
+            errors: vec![Error::end_of_input(), Error::Expected("4 bytes for u32".to_string().into())]
+        }));
+        let result = be_u16().parse(from_slice(&b""[..]));
+        assert_eq!(result.map_err(|err| err.position), Err(BytePosition { position: 0 }));
+    }
+
+    #[test]
+    fn leb128() {
+        assert_eq!(leb128_u64().parse(from_slice(&b"\x00"[..])).map(|x| x.0), Ok(0));
+        assert_eq!(leb128_u64().parse(from_slice(&b"\x7F\x01"[..])), Ok((127, from_slice(&b"\x01"[..]))));
+        assert_eq!(leb128_u64().parse(from_slice(&b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x01"[..])).map(|x| x.0), Ok(u64::max_value()));
+        assert_eq!(leb128_i64().parse(from_slice(&b"\x7F"[..])).map(|x| x.0), Ok(-1));
+        assert_eq!(leb128_i32().parse(from_slice(&b"\x80\x80\x80\x80\x78"[..])).map(|x| x.0), Ok(i32::min_value()));
+        assert_eq!(leb128_i32().parse(from_slice(&b"\xFF\xFF\xFF\xFF\x07"[..])).map(|x| x.0), Ok(i32::max_value()));
+    }
+
+    #[test]
+    fn leb128_overflow() {
+        assert!(leb128_u64().parse(from_slice(&b"\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF\x02"[..])).is_err());
+        assert!(leb128_u32().parse(from_slice(&b"\x80\x80\x80\x80\x80\x00"[..])).is_err());
+        assert!(leb128_i32().parse(from_slice(&b"\xFF\xFF\xFF\xFF\x08"[..])).is_err());
+        let result = leb128_u32().parse(from_slice(&b"\x80\x80"[..]));
+        assert_eq!(result.map_err(|err| err.position), Err(BytePosition { position: 0 }));
+    }
+
+    #[test]
+    fn byte_slice() {
+        use range::take;
+        let mut parser = (byte(b'['), take(2), be_u16(), bytes(b"]"));
+        assert_eq!(parser.parse(&b"[ab\x01\x02]!"[..]), Ok(((b'[', &b"ab"[..], 0x0102, &b"]"[..]), &b"!"[..])));
+        let result = many1::<Vec<_>, _>(digit()).skip(letter()).parse(&b"12a3"[..]);
+        assert_eq!(result, Ok((b"12".to_vec(), &b"3"[..])));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn byte_slice_regex() {
+        use range::regex;
+        let mut parser = (regex("[a-z]+").unwrap(), spaces(), leb128_u32());
+        assert_eq!(parser.parse(&b"abc \x7F"[..]), Ok(((&b"abc"[..], (), 127), &b""[..])));
+    }
+
+    #[test]
+    fn bytes_cmp_case_insensitive() {
+        let mut parser = bytes_cmp(b"Content-Length", |l: u8, r: u8| l.eq_ignore_ascii_case(&r));
+        assert_eq!(parser.parse(from_slice(&b"content-length: 3"[..])).map(|x| x.0), Ok(&b"Content-Length"[..]));
+    }
+}
@@ -1,6 +1,7 @@
-use primitives::{Consumed, Parser, ParseError, ParseResult, Error, State, Stream};
+use primitives::{Consumed, Info, Parser, ParseError, ParseResult, Error, Resumable, State, Stream};
 use combinator::{Expected, satisfy, Satisfy, skip_many, SkipMany, token, Token, ParserExt, With};
 use std::marker::PhantomData;
+use std::cell::Cell;
 
 macro_rules! impl_char_parser {
     ($name: ident ($($ty_var: ident),*), $inner_type: ty) => {
@@ -18,6 +19,14 @@ macro_rules! impl_char_parser {
             self.0.add_error(errors)
         }
     }
+    impl <I $(,$ty_var)*> Resumable for $name<I $(,$ty_var)*>
+        where I: Stream<Item=char> $(, $ty_var : Parser<Input=I>)*
+            , $inner_type: Resumable<Input=I> {
+        type PartialState = <$inner_type as Resumable>::PartialState;
+        fn parse_resume(&mut self, input: State<I>, state: &mut Self::PartialState) -> ParseResult<<Self as Parser>::Output, I> {
+            self.0.parse_resume(input, state)
+        }
+    }
 }
 }
 
@@ -164,10 +173,11 @@ impl <I> Parser for String<I>
                     input = rest.into_inner();
                 }
                 Err(error) => {
-                    return error.combine(|mut error| {
+                    let error = error.map(|mut error| {
                         error.position = start;
-                        Err(if consumed { Consumed::Consumed(error) } else { Consumed::Empty(error) })
-                    })
+                        error
+                    });
+                    return Err(if consumed { error.as_consumed() } else { error })
                 }
             }
         }
@@ -196,6 +206,773 @@ pub fn string<I>(s: &'static str) -> String<I>
 }
 
 
+//Scans the characters of a numeric literal one at a time, remembering the text that was scanned,
+//whether any input has been consumed and whether the end of a partial stream was reached
+struct Scanner<I>
+    where I: Stream<Item=char> {
+    input: State<I>,
+    text: ::std::string::String,
+    consumed: bool,
+    partial: Cell<bool>
+}
+
+impl <I> Scanner<I>
+    where I: Stream<Item=char> {
+    fn new(input: State<I>) -> Scanner<I> {
+        Scanner {
+            input: input,
+            text: ::std::string::String::new(),
+            consumed: false,
+            partial: Cell::new(false)
+        }
+    }
+
+    fn peek(&self, n: usize) -> Option<char> {
+        let mut input = self.input.input.clone();
+        for _ in 0..n {
+            match input.clone().uncons() {
+                Ok((_, rest)) => input = rest,
+                Err(_) => {
+                    self.reached_end(&input);
+                    return None
+                }
+            }
+        }
+        match input.clone().uncons() {
+            Ok((c, _)) => Some(c),
+            Err(_) => {
+                self.reached_end(&input);
+                None
+            }
+        }
+    }
+
+    //Remembers if `input` is the end of a partial stream as more input could then change the
+    //result of the scan
+    fn reached_end(&self, input: &I) {
+        if input.is_partial() {
+            self.partial.set(true);
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        match self.input.clone().uncons() {
+            Ok((c, rest)) => {
+                self.input = rest.into_inner();
+                self.text.push(c);
+                self.consumed = true;
+                Some(c)
+            }
+            Err(_) => {
+                self.reached_end(&self.input.input);
+                None
+            }
+        }
+    }
+
+    fn eat<F>(&mut self, predicate: F) -> bool
+        where F: FnOnce(char) -> bool {
+        match self.peek(0) {
+            Some(c) if predicate(c) => {
+                self.bump();
+                true
+            }
+            _ => false
+        }
+    }
+
+    //Scans digits in `radix`, passing the value of each digit to `f`, and returns the number of
+    //digits. If `separators` is true any `_` between the digits are skipped as well.
+    fn digits<F>(&mut self, radix: u32, separators: bool, mut f: F) -> usize
+        where F: FnMut(u32) {
+        let mut count = 0;
+        loop {
+            match self.peek(0) {
+                Some('_') if separators => (),
+                Some(c) => match c.to_digit(radix) {
+                    Some(d) => {
+                        f(d);
+                        count += 1;
+                    }
+                    None => return count
+                },
+                None => return count
+            }
+            self.bump();
+        }
+    }
+
+    //Fails at the current position, if nothing has been consumed the error is left empty so that
+    //the parser adds its own errors in `add_error`
+    fn error<T, E>(self, expected: E) -> ParseResult<T, I>
+        where E: Into<Info<char, I::Range>> {
+        if self.partial.get() {
+            return Err(Consumed::Partial(ParseError::new(self.input.position, Error::end_of_input())))
+        }
+        if !self.consumed {
+            return Err(Consumed::Empty(ParseError::empty(self.input.position)))
+        }
+        let mut error = ParseError::empty(self.input.position);
+        match self.input.input.uncons() {
+            Ok((c, _)) => error.add_error(Error::Unexpected(Info::Token(c))),
+            Err(err) => error.add_error(err)
+        }
+        error.add_error(Error::Expected(expected.into()));
+        Err(Consumed::Consumed(error))
+    }
+
+    fn finish<T>(self, value: T) -> ParseResult<T, I> {
+        if self.partial.get() {
+            return Err(Consumed::Partial(ParseError::new(self.input.position, Error::end_of_input())))
+        }
+        Ok((value, if self.consumed { Consumed::Consumed(self.input) } else { Consumed::Empty(self.input) }))
+    }
+}
+
+fn digit_name(radix: u32) -> &'static str {
+    match radix {
+        2 => "binary digit",
+        8 => "octal digit",
+        16 => "hexadecimal digit",
+        _ => "digit"
+    }
+}
+
+#[derive(Clone)]
+pub struct Integer<I, T> {
+    radix: u32,
+    prefix: Option<&'static str>,
+    min: i128,
+    max: i128,
+    name: &'static str,
+    convert: fn (i128) -> T,
+    _marker: PhantomData<fn (I) -> I>
+}
+
+impl <I, T> Integer<I, T> {
+    fn expected_info<U, R>(&self) -> Info<U, R> {
+        Info::Borrowed(match self.radix {
+            2 => "binary integer",
+            8 => "octal integer",
+            16 => "hexadecimal integer",
+            _ => "integer"
+        })
+    }
+}
+
+impl <I, T> Parser for Integer<I, T>
+    where I: Stream<Item=char> {
+    type Input = I;
+    type Output = T;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<T, I> {
+        let start = input.position.clone();
+        let mut scanner = Scanner::new(input);
+        let negative = self.min < 0 && scanner.eat(|c| c == '-');
+        if let Some(prefix) = self.prefix {
+            for expected in prefix.chars() {
+                if !scanner.eat(|c| c.to_ascii_lowercase() == expected) {
+                    return scanner.error(prefix)
+                }
+            }
+        }
+        let radix = self.radix;
+        let limit = if negative { -self.min } else { self.max };
+        let mut value: i128 = 0;
+        let mut overflow = false;
+        let count = scanner.digits(radix, self.prefix.is_some(), |d| {
+            value = value * radix as i128 + d as i128;
+            if value > limit {
+                //Keep the value bounded so that arbitrarily long literals can't overflow `value`
+                overflow = true;
+                value = limit;
+            }
+        });
+        if count == 0 {
+            return scanner.error(digit_name(radix))
+        }
+        if overflow {
+            let error = Error::Message(format!("integer literal overflows {}", self.name).into());
+            return Err(Consumed::Consumed(ParseError::new(start, error)))
+        }
+        scanner.finish((self.convert)(if negative { -value } else { value }))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected(self.expected_info()));
+    }
+}
+
+macro_rules! integer_parser {
+    ($(#[$attr: meta])* $name: ident, $ty: ident, $radix: expr, $prefix: expr) => {
+        $(#[$attr])*
+        pub fn $name<I>() -> Integer<I, $ty>
+            where I: Stream<Item=char> {
+            fn convert(value: i128) -> $ty { value as $ty }
+            Integer {
+                radix: $radix,
+                prefix: $prefix,
+                min: <$ty>::MIN as i128,
+                max: <$ty>::MAX as i128,
+                name: stringify!($ty),
+                convert: convert,
+                _marker: PhantomData
+            }
+        }
+    }
+}
+
+integer_parser! {
+    ///Parses a decimal `u8`, failing if the value does not fit in a `u8`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::char::u8;
+    /// # fn main() {
+    /// assert_eq!(u8().parse("255").map(|x| x.0), Ok(255));
+    /// let error = u8().parse("256").unwrap_err();
+    /// assert_eq!(error.position.column, 1);
+    /// # }
+    /// ```
+    u8, u8, 10, None
+}
+integer_parser! {
+    ///Parses a decimal `u16`, failing if the value does not fit in a `u16`
+    u16, u16, 10, None
+}
+integer_parser! {
+    ///Parses a decimal `u32`, failing if the value does not fit in a `u32`
+    u32, u32, 10, None
+}
+integer_parser! {
+    ///Parses a decimal `u64`, failing if the value does not fit in a `u64`
+    u64, u64, 10, None
+}
+integer_parser! {
+    ///Parses a decimal `i8` with an optional leading `-`, failing if the value does not fit in an
+    ///`i8`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::char::i8;
+    /// # fn main() {
+    /// assert_eq!(i8().parse("-128").map(|x| x.0), Ok(-128));
+    /// assert!(i8().parse("128").is_err());
+    /// # }
+    /// ```
+    i8, i8, 10, None
+}
+integer_parser! {
+    ///Parses a decimal `i16` with an optional leading `-`, failing if the value does not fit in an
+    ///`i16`
+    i16, i16, 10, None
+}
+integer_parser! {
+    ///Parses a decimal `i32` with an optional leading `-`, failing if the value does not fit in an
+    ///`i32`
+    i32, i32, 10, None
+}
+integer_parser! {
+    ///Parses a decimal `i64` with an optional leading `-`, failing if the value does not fit in an
+    ///`i64`
+    i64, i64, 10, None
+}
+integer_parser! {
+    ///Parses a hexadecimal integer literal such as `0xFF` or `0x7fff_ffff`, failing if the value
+    ///does not fit in a `u64`.
+    ///The prefix may be written in either case and `_` may be used to separate the digits.
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::char::hex_integer;
+    /// # fn main() {
+    /// assert_eq!(hex_integer().parse("0xdead_BEEF").map(|x| x.0), Ok(0xdeadbeef));
+    /// assert!(hex_integer().parse("0x_").is_err());
+    /// # }
+    /// ```
+    hex_integer, u64, 16, Some("0x")
+}
+integer_parser! {
+    ///Parses an octal integer literal such as `0o755`, failing if the value does not fit in a
+    ///`u64`.
+    ///The prefix may be written in either case and `_` may be used to separate the digits.
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::char::oct_integer;
+    /// # fn main() {
+    /// assert_eq!(oct_integer().parse("0o7_55").map(|x| x.0), Ok(0o755));
+    /// # }
+    /// ```
+    oct_integer, u64, 8, Some("0o")
+}
+integer_parser! {
+    ///Parses a binary integer literal such as `0b1010_0101`, failing if the value does not fit in
+    ///a `u64`.
+    ///The prefix may be written in either case and `_` may be used to separate the digits.
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::char::bin_integer;
+    /// # fn main() {
+    /// assert_eq!(bin_integer().parse("0b1010_0101").map(|x| x.0), Ok(0b1010_0101));
+    /// # }
+    /// ```
+    bin_integer, u64, 2, Some("0b")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FloatSyntax {
+    Rust,
+    Json,
+    C
+}
+
+#[derive(Clone)]
+pub struct Float<I>(FloatSyntax, PhantomData<fn (I) -> I>);
+impl <I> Float<I>
+    where I: Stream<Item=char> {
+    fn exponent(scanner: &mut Scanner<I>, separators: bool) -> bool {
+        if scanner.eat(|c| c == 'e' || c == 'E') {
+            scanner.eat(|c| c == '+' || c == '-');
+            return scanner.digits(10, separators, |_| ()) != 0
+        }
+        true
+    }
+}
+
+impl <I> Parser for Float<I>
+    where I: Stream<Item=char> {
+    type Input = I;
+    type Output = f64;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<f64, I> {
+        let mut scanner = Scanner::new(input);
+        let mut suffix = false;
+        match self.0 {
+            FloatSyntax::Rust => {
+                scanner.eat(|c| c == '-');
+                if !scanner.peek(0).map_or(false, |c| c.is_digit(10)) {
+                    return scanner.error("digit")
+                }
+                scanner.digits(10, true, |_| ());
+                //Only treat `.` as part of the literal if a digit follows so that ranges such as
+                //`1..2` and method calls such as `1.max(2)` are left alone
+                if scanner.peek(0) == Some('.') && scanner.peek(1).map_or(false, |c| c.is_digit(10)) {
+                    scanner.bump();
+                    scanner.digits(10, true, |_| ());
+                }
+                if !Float::exponent(&mut scanner, true) {
+                    return scanner.error("digit")
+                }
+            }
+            FloatSyntax::Json => {
+                scanner.eat(|c| c == '-');
+                if !scanner.eat(|c| c == '0') && scanner.digits(10, false, |_| ()) == 0 {
+                    return scanner.error("digit")
+                }
+                if scanner.eat(|c| c == '.') && scanner.digits(10, false, |_| ()) == 0 {
+                    return scanner.error("digit")
+                }
+                if !Float::exponent(&mut scanner, false) {
+                    return scanner.error("digit")
+                }
+            }
+            FloatSyntax::C => {
+                scanner.eat(|c| c == '+' || c == '-');
+                let mut count = scanner.digits(10, false, |_| ());
+                if scanner.eat(|c| c == '.') {
+                    count += scanner.digits(10, false, |_| ());
+                }
+                if count == 0 {
+                    return scanner.error("digit")
+                }
+                if !Float::exponent(&mut scanner, false) {
+                    return scanner.error("digit")
+                }
+                suffix = scanner.eat(|c| c == 'f' || c == 'F' || c == 'l' || c == 'L');
+            }
+        }
+        let mut text: ::std::string::String = scanner.text.chars()
+            .filter(|&c| c != '_')
+            .collect();
+        if suffix {
+            text.pop();
+        }
+        match text.parse() {
+            Ok(value) => scanner.finish(value),
+            Err(err) => {
+                let error = Error::Message(format!("{}", err).into());
+                Err(Consumed::Consumed(ParseError::new(scanner.input.position, error)))
+            }
+        }
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected("float".into()));
+    }
+}
+
+///Parses a floating point number written as a Rust literal such as `1`, `-2.5` or `1_000.0e-3`.
+///A leading `-` is accepted, digits may be separated by `_` and a `.` is only parsed if it is
+///followed by a digit.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::char::float;
+/// # fn main() {
+/// assert_eq!(float().parse("1_000.5e-1").map(|x| x.0), Ok(100.05));
+/// assert_eq!(float().parse("1..2"), Ok((1.0, "..2")));
+/// # }
+/// ```
+pub fn float<I>() -> Float<I>
+    where I: Stream<Item=char> {
+    Float(FloatSyntax::Rust, PhantomData)
+}
+
+///Parses a number as specified by JSON such as `0`, `-12.5` or `1E+10`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::char::json_number;
+/// # fn main() {
+/// assert_eq!(json_number().parse("-12.5e2").map(|x| x.0), Ok(-1250.0));
+/// //Leading zeros are not part of the number
+/// assert_eq!(json_number().parse("012"), Ok((0.0, "12")));
+/// assert!(json_number().parse("1.").is_err());
+/// # }
+/// ```
+pub fn json_number<I>() -> Float<I>
+    where I: Stream<Item=char> {
+    Float(FloatSyntax::Json, PhantomData)
+}
+
+///Parses a floating point number written as a C literal such as `.5`, `1.`, `+3e8` or `2.5f`.
+///A leading sign is accepted and a trailing `f`, `F`, `l` or `L` suffix is skipped.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::char::c_float;
+/// # fn main() {
+/// assert_eq!(c_float().parse(".5f").map(|x| x.0), Ok(0.5));
+/// assert_eq!(c_float().parse("1.e3").map(|x| x.0), Ok(1000.0));
+/// # }
+/// ```
+pub fn c_float<I>() -> Float<I>
+    where I: Stream<Item=char> {
+    Float(FloatSyntax::C, PhantomData)
+}
+
+///The escape sequences which are recognized by `string_literal`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Escapes {
+    ///The escapes of C: `\a \b \f \n \r \t \v \\ \' \" \?`, octal escapes such as `\0` or `\101`,
+    ///hexadecimal escapes such as `\x41` as well as `\uXXXX` and `\UXXXXXXXX`.
+    ///Raw newlines are not allowed in the string.
+    C,
+    ///The escapes of JSON: `\" \\ \/ \b \f \n \r \t` and `\uXXXX` where characters outside the basic
+    ///multilingual plane are written as a surrogate pair, for instance `\ud83d\ude00`.
+    ///Control characters are not allowed in the string.
+    Json,
+    ///The escapes of Rust: `\n \r \t \\ \0 \' \"`, ASCII escapes such as `\x7F`, unicode escapes
+    ///such as `\u{1F600}` and a `\` at the end of a line which skips the newline and any leading
+    ///whitespace on the next line.
+    Rust
+}
+
+fn invalid_escape<I>(position: <I::Item as ::primitives::Positioner>::Position, text: &str) -> ParseError<I>
+    where I: Stream<Item=char> {
+    ParseError::new(position, Error::Message(format!("Invalid escape sequence `{}`", text).into()))
+}
+
+//Scans at least `min` and at most `max` hexadecimal digits, returning `None` if there were too
+//few digits or the value is larger than `limit`
+fn hex_escape<I>(scanner: &mut Scanner<I>, min: usize, max: usize, limit: u32) -> Option<u32>
+    where I: Stream<Item=char> {
+    let mut value = 0u32;
+    let mut count = 0;
+    while count < max {
+        match scanner.peek(0).and_then(|c| c.to_digit(16)) {
+            Some(d) => {
+                scanner.bump();
+                value = (value * 16 + d).min(limit + 1);
+                count += 1;
+            }
+            None => break
+        }
+    }
+    if count < min || value > limit { None } else { Some(value) }
+}
+
+#[derive(Clone)]
+pub struct StringLiteral<I> {
+    quotes: &'static str,
+    escapes: Escapes,
+    _marker: PhantomData<fn (I) -> I>
+}
+
+impl <I> StringLiteral<I>
+    where I: Stream<Item=char> {
+    //Parses the escape sequence after a `\`, returning `None` if it produced no character
+    fn escape(&self, scanner: &mut Scanner<I>, quote: char) -> Result<Option<char>, ParseError<I>> {
+        let position = scanner.input.position.clone();
+        let mark = scanner.text.len();
+        scanner.bump();
+        let c = match scanner.bump() {
+            Some(c) => c,
+            None => {
+                let mut error = ParseError::end_of_input(position);
+                error.add_error(Error::Expected("escape sequence".into()));
+                return Err(error)
+            }
+        };
+        let escapes = self.escapes;
+        let simple = match (escapes, c) {
+            (_, '\\') => Some('\\'),
+            (_, '"') => Some('"'),
+            (_, 'n') => Some('\n'),
+            (_, 'r') => Some('\r'),
+            (_, 't') => Some('\t'),
+            (Escapes::C, '\'') | (Escapes::Rust, '\'') => Some('\''),
+            (Escapes::C, 'b') | (Escapes::Json, 'b') => Some('\u{8}'),
+            (Escapes::C, 'f') | (Escapes::Json, 'f') => Some('\u{c}'),
+            (Escapes::C, 'a') => Some('\u{7}'),
+            (Escapes::C, 'v') => Some('\u{b}'),
+            (Escapes::C, '?') => Some('?'),
+            (Escapes::Json, '/') => Some('/'),
+            (Escapes::Rust, '0') => Some('\0'),
+            (_, c) if c == quote => Some(quote),
+            _ => None
+        };
+        if simple.is_some() {
+            return Ok(simple)
+        }
+        let value = match (escapes, c) {
+            (Escapes::C, '0'..='7') => {
+                let mut value = c.to_digit(8).unwrap();
+                for _ in 0..2 {
+                    match scanner.peek(0).and_then(|c| c.to_digit(8)) {
+                        Some(d) => {
+                            scanner.bump();
+                            value = value * 8 + d;
+                        }
+                        None => break
+                    }
+                }
+                if value > 0xFF { None } else { Some(value) }
+            }
+            (Escapes::C, 'x') => hex_escape(scanner, 1, usize::max_value(), 0xFF),
+            (Escapes::C, 'u') => hex_escape(scanner, 4, 4, 0xFFFF),
+            (Escapes::C, 'U') => hex_escape(scanner, 8, 8, 0x10FFFF),
+            (Escapes::Json, 'u') => {
+                match hex_escape(scanner, 4, 4, 0xFFFF) {
+                    Some(high @ 0xD800..=0xDBFF) => {
+                        //A high surrogate must be followed by an escaped low surrogate
+                        let low = if scanner.peek(0) == Some('\\') && scanner.peek(1) == Some('u') {
+                            scanner.bump();
+                            scanner.bump();
+                            hex_escape(scanner, 4, 4, 0xFFFF)
+                        } else {
+                            None
+                        };
+                        match low {
+                            Some(low @ 0xDC00..=0xDFFF) => {
+                                Some(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                            }
+                            _ => None
+                        }
+                    }
+                    value => value
+                }
+            }
+            (Escapes::Rust, 'x') => hex_escape(scanner, 2, 2, 0x7F),
+            (Escapes::Rust, 'u') => {
+                if scanner.eat(|c| c == '{') {
+                    let mut value = 0u32;
+                    let mut count = 0;
+                    loop {
+                        match scanner.peek(0) {
+                            Some('_') if count != 0 => (),
+                            Some(c) => match c.to_digit(16) {
+                                Some(d) if count < 6 => {
+                                    value = value * 16 + d;
+                                    count += 1;
+                                }
+                                _ => break
+                            },
+                            None => break
+                        }
+                        scanner.bump();
+                    }
+                    if count != 0 && scanner.eat(|c| c == '}') { Some(value) } else { None }
+                } else {
+                    None
+                }
+            }
+            (Escapes::Rust, '\n') | (Escapes::Rust, '\r') => {
+                while scanner.eat(char::is_whitespace) {}
+                return Ok(None)
+            }
+            _ => None
+        };
+        match value.and_then(::std::char::from_u32) {
+            Some(c) => Ok(Some(c)),
+            None => Err(invalid_escape(position, &scanner.text[mark..]))
+        }
+    }
+}
+
+impl <I> Parser for StringLiteral<I>
+    where I: Stream<Item=char> {
+    type Input = I;
+    type Output = ::std::string::String;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<::std::string::String, I> {
+        let mut scanner = Scanner::new(input);
+        let quotes = self.quotes;
+        let quote = match scanner.peek(0) {
+            Some(c) if quotes.contains(c) => c,
+            _ => return scanner.error("string literal")
+        };
+        scanner.bump();
+        let mut value = ::std::string::String::new();
+        loop {
+            match scanner.peek(0) {
+                Some(c) if c == quote => {
+                    scanner.bump();
+                    return scanner.finish(value)
+                }
+                Some('\\') => {
+                    match self.escape(&mut scanner, quote) {
+                        Ok(Some(c)) => value.push(c),
+                        Ok(None) => (),
+                        Err(err) => return Err(Consumed::Consumed(err))
+                    }
+                }
+                Some(c) if (self.escapes == Escapes::Json && c < ' ') ||
+                           (self.escapes == Escapes::C && c == '\n') => {
+                    return scanner.error(quote)
+                }
+                Some(c) => {
+                    scanner.bump();
+                    value.push(c);
+                }
+                None => return scanner.error(quote)
+            }
+        }
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected("string literal".into()));
+    }
+}
+
+///Parses a string literal which starts and ends with the same character out of `quotes`,
+///returning the string with all escape sequences in `escapes` replaced by the characters they
+///represent.
+///If an escape sequence is invalid the error is positioned at its `\`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::char::{string_literal, Escapes};
+/// # fn main() {
+/// let mut parser = string_literal("\"'", Escapes::Rust);
+/// assert_eq!(parser.parse(r#""a\tb\u{1F600}""#).map(|x| x.0), Ok("a\tb\u{1F600}".to_string()));
+/// assert_eq!(parser.parse(r#"'say "hi"'"#).map(|x| x.0), Ok("say \"hi\"".to_string()));
+/// let error = parser.parse(r#""ab\q""#).unwrap_err();
+/// assert_eq!(error.position.column, 4);
+/// # }
+/// ```
+pub fn string_literal<I>(quotes: &'static str, escapes: Escapes) -> StringLiteral<I>
+    where I: Stream<Item=char> {
+    StringLiteral { quotes: quotes, escapes: escapes, _marker: PhantomData }
+}
+
+///Parses a JSON string, equivalent to `string_literal("\"", Escapes::Json)`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::char::json_string;
+/// # fn main() {
+/// assert_eq!(json_string().parse(r#""\ud83d\ude00\/""#).map(|x| x.0), Ok("\u{1F600}/".to_string()));
+/// assert!(json_string().parse(r#""\ud83d""#).is_err());
+/// # }
+/// ```
+pub fn json_string<I>() -> StringLiteral<I>
+    where I: Stream<Item=char> {
+    string_literal("\"", Escapes::Json)
+}
+
+#[derive(Clone)]
+pub struct RawString<I>(PhantomData<fn (I) -> I>);
+impl <I> Parser for RawString<I>
+    where I: Stream<Item=char> {
+    type Input = I;
+    type Output = ::std::string::String;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<::std::string::String, I> {
+        let mut scanner = Scanner::new(input);
+        if scanner.peek(0) != Some('r') {
+            return scanner.error("raw string literal")
+        }
+        let mut hashes = 0;
+        while scanner.peek(hashes + 1) == Some('#') {
+            hashes += 1;
+        }
+        //Look ahead for the opening quote so that identifiers starting with `r` are not consumed
+        if scanner.peek(hashes + 1) != Some('"') {
+            return scanner.error("raw string literal")
+        }
+        for _ in 0..hashes + 2 {
+            scanner.bump();
+        }
+        let mut value = ::std::string::String::new();
+        loop {
+            match scanner.bump() {
+                Some('"') => {
+                    let mut closing = 0;
+                    while closing < hashes && scanner.eat(|c| c == '#') {
+                        closing += 1;
+                    }
+                    if closing == hashes {
+                        return scanner.finish(value)
+                    }
+                    value.push('"');
+                    value.extend(::std::iter::repeat('#').take(closing));
+                }
+                Some(c) => value.push(c),
+                None => return scanner.error('"')
+            }
+        }
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected("raw string literal".into()));
+    }
+}
+
+///Parses a Rust raw string literal such as `r"C:\dir"` or `r#"say "hi""#` where no escape
+///sequences are processed.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::char::raw_string;
+/// # fn main() {
+/// assert_eq!(raw_string().parse(r###"r##"a "# b"##"###).map(|x| x.0), Ok(r##"a "# b"##.to_string()));
+/// //Nothing is consumed unless the input starts a raw string
+/// assert!(raw_string().or(many1(letter())).parse("rust").is_ok());
+/// # }
+/// ```
+pub fn raw_string<I>() -> RawString<I>
+    where I: Stream<Item=char> {
+    RawString(PhantomData)
+}
+
+impl_restart!(String<I>, Integer<I, T>, Float<I>, StringLiteral<I>, RawString<I>);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,15 +991,108 @@ mod tests {
     fn string_consumed() {
         let result = string("a").parse("b");
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err().position, SourcePosition { line: 1, column: 1 });
+        assert_eq!(result.unwrap_err().position, SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 });
     }
 
     #[test]
     fn string_error() {
         let result = string("abc").parse("bc");
         assert_eq!(result, Err(ParseError {
-            position: SourcePosition { line: 1, column: 1 },
+            position: SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 },
             errors: vec![Error::Unexpected('b'.into()), Error::Expected("abc".into())]
         }));
     }
+
+    #[test]
+    fn integer_overflow() {
+        let result = ::combinator::ParserExt::skip(spaces(), char('[')).with(i16()).parse("  [-32769]");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 4, offset: 3, utf16_column: 4 },
+            errors: vec![Error::Message("integer literal overflows i16".into())]
+        }));
+        assert_eq!(i16().parse("-32768]").map(|x| x.0), Ok(-32768));
+        assert_eq!(u64().parse("18446744073709551615").map(|x| x.0), Ok(u64::max_value()));
+        assert!(u64().parse("184467440737095516150000000000000000000000").is_err());
+    }
+
+    #[test]
+    fn integer_errors() {
+        assert_eq!(u8().parse("-1"), Err(ParseError {
+            position: SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 },
+            errors: vec![Error::Unexpected('-'.into()), Error::Expected("integer".into())]
+        }));
+        assert_eq!(i32().parse("-a"), Err(ParseError {
+            position: SourcePosition { line: 1, column: 2, offset: 1, utf16_column: 2 },
+            errors: vec![Error::Unexpected('a'.into()), Error::Expected("digit".into())]
+        }));
+        assert_eq!(hex_integer().parse("0xg"), Err(ParseError {
+            position: SourcePosition { line: 1, column: 3, offset: 2, utf16_column: 3 },
+            errors: vec![Error::Unexpected('g'.into()), Error::Expected("hexadecimal digit".into())]
+        }));
+        assert!(hex_integer().parse("0x1_0000_0000_0000_0000").is_err());
+        assert_eq!(bin_integer().parse("0B_1_1 ").map(|x| x.0), Ok(3));
+    }
+
+    #[test]
+    fn float_syntaxes() {
+        assert_eq!(float().parse("2.5E3").map(|x| x.0), Ok(2500.0));
+        assert_eq!(float().parse("1.max(2)"), Ok((1.0, ".max(2)")));
+        assert_eq!(json_number().parse("-0.25"), Ok((-0.25, "")));
+        assert_eq!(json_number().parse("1e+2,"), Ok((100.0, ",")));
+        assert_eq!(c_float().parse("-1.5e-1L;"), Ok((-0.15, ";")));
+        assert!(c_float().parse(".").is_err());
+        assert_eq!(json_number().parse("1e"), Err(ParseError {
+            position: SourcePosition { line: 1, column: 3, offset: 2, utf16_column: 3 },
+            errors: vec![Error::end_of_input(), Error::Expected("digit".into())]
+        }));
+    }
+
+    #[test]
+    fn string_literal_escapes() {
+        let mut c = string_literal("\"", Escapes::C);
+        assert_eq!(c.parse(r#""\101\x42\?\a\u00e9""#).map(|x| x.0), Ok("AB?\u{7}\u{e9}".to_string()));
+        assert!(c.parse("\"a\nb\"").is_err());
+        let mut rust = string_literal("\"", Escapes::Rust);
+        assert_eq!(rust.parse("\"a\\\n    b\\0\"").map(|x| x.0), Ok("ab\0".to_string()));
+        assert!(rust.parse(r#""\x80""#).is_err());
+        assert!(rust.parse(r#""\u{D800}""#).is_err());
+        let mut json = json_string();
+        assert_eq!(json.parse(r#""\u00e9\ud834\udd1e""#).map(|x| x.0), Ok("\u{e9}\u{1D11E}".to_string()));
+        assert!(json.parse(r#""\'""#).is_err());
+        assert!(json.parse("\"\t\"").is_err());
+    }
+
+    #[test]
+    fn string_literal_errors() {
+        let result = json_string().parse("\"a\nb \\udc00\"");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 3, offset: 2, utf16_column: 3 },
+            errors: vec![Error::Unexpected('\n'.into()), Error::Expected('"'.into())]
+        }));
+        let result = string_literal("\"", Escapes::Rust).parse("\"a\nb \\udc00\"");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 2, column: 3, offset: 5, utf16_column: 3 },
+            errors: vec![Error::Message("Invalid escape sequence `\\u`".into())]
+        }));
+        let result = json_string().parse("\"\\ud800\\u0041\"");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 2, offset: 1, utf16_column: 2 },
+            errors: vec![Error::Message("Invalid escape sequence `\\ud800\\u0041`".into())]
+        }));
+        let result = json_string().parse("'a'");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 },
+            errors: vec![Error::Unexpected('\''.into()), Error::Expected("string literal".into())]
+        }));
+    }
+
+    #[test]
+    fn raw_string_unterminated() {
+        assert_eq!(raw_string().parse("r#\"a\"b\"#c"), Ok(("a\"b".to_string(), "c")));
+        let result = raw_string().parse("r#\"a\"");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 6, offset: 5, utf16_column: 6 },
+            errors: vec![Error::end_of_input(), Error::Expected('"'.into())]
+        }));
+    }
 }
@@ -1,14 +1,20 @@
 use std::iter::FromIterator;
 use std::marker::PhantomData;
-use primitives::{Info, Parser, ParseResult, ParseError, Positioner, Stream, State, Error, Consumed};
+use std::mem;
+use primitives::{Info, Parser, ParseResult, ParseError, Positioner, RecoverStream, Resumable, Span,
+                 StatefulStream, Stream, State, Error, Consumed};
+use trace::{trace, Trace};
 
 macro_rules! impl_parser {
     ($name: ident ($first: ident, $($ty_var: ident),*), $inner_type: ty) => {
+        impl_parser!{ $name($first, $($ty_var),*) [], $inner_type }
+    };
+    ($name: ident ($first: ident, $($ty_var: ident),*) [$($extra: ident : $extra_bound: path),*], $inner_type: ty) => {
     #[derive(Clone)]
-    pub struct $name<$first $(,$ty_var)*>($inner_type)
+    pub struct $name<$($extra,)* $first $(,$ty_var)*>($inner_type)
         where $first: Parser $(,$ty_var : Parser<Input=<$first as Parser>::Input>)*;
-    impl <$first, $($ty_var),*> Parser for $name<$first $(,$ty_var)*>
-        where $first: Parser $(, $ty_var : Parser<Input=<$first as Parser>::Input>)* {
+    impl <$($extra,)* $first, $($ty_var),*> Parser for $name<$($extra,)* $first $(,$ty_var)*>
+        where $first: Parser $(, $ty_var : Parser<Input=<$first as Parser>::Input>)* $(, $extra: $extra_bound)* {
         type Input = <$first as Parser>::Input;
         type Output = <$inner_type as Parser>::Output;
         fn parse_state(&mut self, input: State<<Self as Parser>::Input>) -> ParseResult<<Self as Parser>::Output, Self::Input> {
@@ -20,6 +26,17 @@ macro_rules! impl_parser {
         fn add_error(&mut self, error: &mut ParseError<Self::Input>) {
             self.0.add_error(error)
         }
+        fn add_trailing_error(&mut self, error: &mut ParseError<Self::Input>) {
+            self.0.add_trailing_error(error)
+        }
+    }
+    impl <$($extra,)* $first, $($ty_var),*> Resumable for $name<$($extra,)* $first $(,$ty_var)*>
+        where $first: Parser $(, $ty_var : Parser<Input=<$first as Parser>::Input>)* $(, $extra: $extra_bound)*
+            , $inner_type: Resumable<Input=<$first as Parser>::Input> {
+        type PartialState = <$inner_type as Resumable>::PartialState;
+        fn parse_resume(&mut self, input: State<Self::Input>, state: &mut Self::PartialState) -> ParseResult<Self::Output, Self::Input> {
+            self.0.parse_resume(input, state)
+        }
     }
 }
 }
@@ -71,7 +88,7 @@ fn satisfy_impl<I, P, F>(input: State<I>, predicate: &mut P, f: F) -> ParseResul
                 Err(Consumed::Empty(f(input.position, c)))
             }
         }
-        Err(err) => Err(Consumed::Empty(ParseError::new(input.position, err)))
+        Err(err) => Err(input.uncons_error(err))
     }
 }
 
@@ -154,7 +171,8 @@ impl <I, O, S, P> Parser for Choice<S, P>
         let mut empty_err = None;
         for p in AsMut::as_mut(&mut self.0) {
             match p.parse_lazy(input.clone()) {
-                consumed_err@Err(Consumed::Consumed(_)) => return consumed_err,
+                consumed_err@Err(Consumed::Consumed(_)) |
+                consumed_err@Err(Consumed::Partial(_)) => return consumed_err,
                 Err(Consumed::Empty(err)) => {
                     empty_err = match empty_err {
                         None => Some(err),
@@ -301,9 +319,11 @@ impl <P: Parser> Iter<P> {
     ///without any errors which consumed data.
     pub fn into_result<O>(self, value: O) -> ParseResult<O, P::Input> {
         match self.error {
-            Some(err@Consumed::Consumed(_)) => Err(err),
-            _ => if self.consumed { Ok((value, Consumed::Consumed(self.input))) }
-                 else { Ok((value, Consumed::Empty(self.input))) }
+            Some(Consumed::Empty(_)) | None => {
+                if self.consumed { Ok((value, Consumed::Consumed(self.input))) }
+                else { Ok((value, Consumed::Empty(self.input))) }
+            }
+            Some(err) => Err(err)
         }
     }
 }
@@ -342,6 +362,46 @@ impl <F, P> Parser for Many<F, P>
     }
 }
 
+//The progress of a suspended repetition, the values parsed so far, the state after the last value
+//and the progress of the value being parsed
+type RepeatState<P> = (Vec<<P as Parser>::Output>, Option<Consumed<State<<P as Parser>::Input>>>, <P as Resumable>::PartialState);
+
+//Parses `parser` until it fails without consuming input, resuming the repetition stored in
+//`state`. If `required` is true `parser` must succeed at least once.
+fn resume_repeat<P>(parser: &mut P, input: State<P::Input>, state: &mut RepeatState<P>, required: bool) -> ParseResult<Vec<P::Output>, P::Input>
+    where P: Resumable {
+    let (mut values, rest, mut child) = mem::replace(state, Default::default());
+    let mut input = rest.unwrap_or(Consumed::Empty(input));
+    loop {
+        match parser.parse_resume(input.clone().into_inner(), &mut child) {
+            Ok((value, rest)) => {
+                values.push(value);
+                input = if input.is_empty() { rest } else { rest.as_consumed() };
+            }
+            Err(Consumed::Empty(err)) => {
+                if required && values.is_empty() {
+                    return Err(Consumed::Empty(err))
+                }
+                return Ok((values, input))
+            }
+            Err(err@Consumed::Partial(_)) => {
+                *state = (values, Some(input), child);
+                return Err(err)
+            }
+            Err(err) => return Err(err)
+        }
+    }
+}
+
+impl <F, P> Resumable for Many<F, P>
+    where P: Resumable, F: FromIterator<<P as Parser>::Output> {
+    type PartialState = RepeatState<P>;
+    fn parse_resume(&mut self, input: State<P::Input>, state: &mut RepeatState<P>) -> ParseResult<F, P::Input> {
+        resume_repeat(&mut self.0, input, state, false)
+            .map(|(values, rest)| (values.into_iter().collect(), rest))
+    }
+}
+
 ///Parses `p` zero or more times returning a collection with the values from `p`.
 ///If the returned collection cannot be inferred type annotations must be supplied, either by
 ///annotating the resulting type binding `let collection: Vec<_> = ...` or by specializing when
@@ -385,6 +445,16 @@ impl <F, P> Parser for Many1<F, P>
     }
 }
 
+impl <F, P> Resumable for Many1<F, P>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Resumable {
+    type PartialState = RepeatState<P>;
+    fn parse_resume(&mut self, input: State<P::Input>, state: &mut RepeatState<P>) -> ParseResult<F, P::Input> {
+        resume_repeat(&mut self.0, input, state, true)
+            .map(|(values, rest)| (values.into_iter().collect(), rest))
+    }
+}
+
 impl_parser!{ SkipMany(P,), Map<Many<Vec<()>, Map<P, fn (<P as Parser>::Output)>>, fn (Vec<()>)> }
 ///Parses `p` zero or more times ignoring the result
 ///
@@ -446,6 +516,132 @@ pub fn many1<F, P>(p: P) -> Many1<F, P>
     Many1(p, PhantomData)
 }
 
+#[derive(Clone)]
+pub struct CountMinMax<F, P> {
+    parser: P,
+    min: usize,
+    max: usize,
+    _marker: PhantomData<fn () -> F>
+}
+
+fn repetitions(min: usize, max: usize, found: usize) -> String {
+    let noun = if min == 1 { "repetition" } else { "repetitions" };
+    if min == max {
+        format!("Expected {} {} but found {}", min, noun, found)
+    }
+    else {
+        format!("Expected at least {} {} but found {}", min, noun, found)
+    }
+}
+
+impl <F, P> Parser for CountMinMax<F, P>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser {
+    type Input = <P as Parser>::Input;
+    type Output = F;
+    fn parse_lazy(&mut self, input: State<<P as Parser>::Input>) -> ParseResult<F, P::Input> {
+        let (min, max) = (self.min, self.max);
+        let mut count = 0;
+        let mut iter = Iter::new(&mut self.parser, input);
+        let value: F = iter.by_ref()
+            .take(max)
+            .inspect(|_| count += 1)
+            .collect();
+        if count >= min {
+            return iter.into_result(value)
+        }
+        let mut error = match iter.error {
+            Some(Consumed::Empty(err)) => {
+                let mut error = err;
+                if count != 0 {
+                    //The caller only completes the error for the first repetition
+                    match iter.input.input.clone().uncons() {
+                        Ok((t, _)) => error.add_error(Error::Unexpected(Info::Token(t))),
+                        Err(err) => error.add_error(err)
+                    }
+                    iter.parser.add_error(&mut error);
+                }
+                error
+            }
+            Some(err) => return Err(err),
+            //The collection stopped taking values before enough repetitions were parsed
+            None => ParseError::empty(iter.input.position.clone())
+        };
+        error.add_error(Error::Message(repetitions(min, max, count).into()));
+        Err(if iter.consumed { Consumed::Consumed(error) } else { Consumed::Empty(error) })
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.parser.add_error(errors)
+    }
+}
+
+///Parses `p` exactly `n` times returning a collection with the values from `p`.
+///If `p` succeeds fewer than `n` times the error states how many repetitions were found.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::primitives::Error;
+/// # fn main() {
+/// let mut parser = count::<String, _>(4, hex_digit());
+/// assert_eq!(parser.parse("00e9ff").map(|x| x.0), Ok("00e9".to_string()));
+/// let error = parser.parse("00e").unwrap_err();
+/// assert!(error.errors.contains(&Error::Message("Expected 4 repetitions but found 3".into())));
+/// # }
+/// ```
+pub fn count<F, P>(n: usize, p: P) -> CountMinMax<F, P>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser {
+    count_min_max(n, n, p)
+}
+
+///Parses `p` at least `min` and at most `max` times returning a collection with the values from
+///`p`.
+///If `p` succeeds fewer than `min` times the error states how many repetitions were found.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = count_min_max::<String, _>(2, 3, oct_digit());
+/// assert_eq!(parser.parse("7777").map(|x| x.0), Ok("777".to_string()));
+/// assert_eq!(parser.parse("12a").map(|x| x.0), Ok("12".to_string()));
+/// assert!(parser.parse("1a").is_err());
+/// # }
+/// ```
+pub fn count_min_max<F, P>(min: usize, max: usize, p: P) -> CountMinMax<F, P>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser {
+    assert!(min <= max, "count_min_max: min must not be larger than max");
+    CountMinMax { parser: p, min: min, max: max, _marker: PhantomData }
+}
+
+impl_parser!{ SkipCountMinMax(P,), Map<CountMinMax<Vec<()>, Map<P, fn (<P as Parser>::Output)>>, fn (Vec<()>)> }
+///Parses `p` exactly `n` times ignoring the result
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # fn main() {
+/// let result = skip_count(2, char('a'))
+///     .parse("aaa");
+/// assert_eq!(result, Ok(((), "a")));
+/// # }
+/// ```
+pub fn skip_count<P>(n: usize, p: P) -> SkipCountMinMax<P>
+    where P: Parser {
+    skip_count_min_max(n, n, p)
+}
+
+///Parses `p` at least `min` and at most `max` times ignoring the result
+pub fn skip_count_min_max<P>(min: usize, max: usize, p: P) -> SkipCountMinMax<P>
+    where P: Parser {
+    fn ignore<T>(_: T) {  }
+    let ignore1: fn (P::Output) = ignore;
+    let ignore2: fn (Vec<()>) = ignore;
+    SkipCountMinMax(count_min_max(min, max, p.map(ignore1)).map(ignore2))
+}
+
 #[derive(Clone)]
 pub struct SepBy<F, P, S> {
     parser: P,
@@ -467,8 +663,8 @@ impl <F, P, S> Parser for SepBy<F, P, S>
                 input = rest;
                 first = x
             }
-            Err(err@Consumed::Consumed(_)) => return Err(err),
-            Err(Consumed::Empty(_)) => return Ok((None.into_iter().collect(), input))
+            Err(Consumed::Empty(_)) => return Ok((None.into_iter().collect(), input)),
+            Err(err) => return Err(err)
         };
 
         let (result, input) = try!(input.combine(move |input| {
@@ -510,6 +706,294 @@ pub fn sep_by<F, P, S>(parser: P, separator: S) -> SepBy<F, P, S>
     SepBy { parser: parser, separator: separator, _marker: PhantomData }
 }
 
+#[derive(Clone)]
+pub struct SepBy1<F, P, S> {
+    parser: P,
+    separator: S,
+    _marker: PhantomData<fn () -> F>
+}
+impl <F, P, S> Parser for SepBy1<F, P, S>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , S: Parser<Input=<P as Parser>::Input> {
+
+    type Input = <P as Parser>::Input;
+    type Output = F;
+    fn parse_lazy(&mut self, input: State<<P as Parser>::Input>) -> ParseResult<F, P::Input> {
+        let (first, input) = try!(self.parser.parse_lazy(input));
+        input.combine(move |input| {
+            let rest = (&mut self.separator)
+                .with(&mut self.parser);
+            let mut iter = Iter::new(rest, input);
+            let result = Some(first).into_iter()
+                .chain(iter.by_ref())
+                .collect();
+            iter.into_result(result)
+        })
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.parser.add_error(errors)
+    }
+}
+
+///Parses `parser` one or more time separated by `separator`, returning a collection with the
+///values from `p`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = sep_by1(digit(), token(','));
+/// let result_ok = parser.parse("1,2,3");
+/// assert_eq!(result_ok, Ok((vec!['1', '2', '3'], "")));
+/// let result_err = parser.parse("");
+/// assert!(result_err.is_err());
+/// # }
+/// ```
+pub fn sep_by1<F, P, S>(parser: P, separator: S) -> SepBy1<F, P, S>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , S: Parser<Input=<P as Parser>::Input> {
+    SepBy1 { parser: parser, separator: separator, _marker: PhantomData }
+}
+
+//Iterator which parses elements followed by an optional separator, stopping after the first
+//element which is not followed by a separator
+struct SepEndByIter<'a, P: 'a, S: 'a>
+    where P: Parser {
+    iter: Iter<&'a mut P>,
+    separator: &'a mut S,
+    done: bool
+}
+
+impl <'a, P, S> Iterator for SepEndByIter<'a, P, S>
+    where P: Parser
+        , S: Parser<Input=P::Input> {
+    type Item = P::Output;
+    fn next(&mut self) -> Option<P::Output> {
+        if self.done {
+            return None
+        }
+        let value = match self.iter.next() {
+            Some(value) => value,
+            None => return None
+        };
+        match self.separator.parse_lazy(self.iter.input.clone()) {
+            Ok((_, rest)) => {
+                self.iter.consumed = self.iter.consumed || !rest.is_empty();
+                self.iter.input = rest.into_inner();
+            }
+            Err(Consumed::Empty(_)) => self.done = true,
+            Err(err) => self.iter.error = Some(err)
+        }
+        Some(value)
+    }
+}
+
+#[derive(Clone)]
+pub struct SepEndBy<F, P, S> {
+    parser: P,
+    separator: S,
+    required: bool,
+    _marker: PhantomData<fn () -> F>
+}
+impl <F, P, S> Parser for SepEndBy<F, P, S>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , S: Parser<Input=<P as Parser>::Input> {
+
+    type Input = <P as Parser>::Input;
+    type Output = F;
+    fn parse_lazy(&mut self, input: State<<P as Parser>::Input>) -> ParseResult<F, P::Input> {
+        let mut found = false;
+        let mut iter = SepEndByIter {
+            iter: Iter::new(&mut self.parser, input),
+            separator: &mut self.separator,
+            done: false
+        };
+        let result = iter.by_ref()
+            .inspect(|_| found = true)
+            .collect();
+        if self.required && !found {
+            //The first element failed which is only an error for `sep_end_by1`
+            if let Some(err) = iter.iter.error {
+                return Err(err)
+            }
+        }
+        iter.iter.into_result(result)
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.parser.add_error(errors)
+    }
+}
+
+///Parses `parser` zero or more times separated and optionally ended by `separator`, returning a
+///collection with the values from `p`.
+///This is the parser to use for lists which allow a trailing separator.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = sep_end_by(digit(), token(','));
+/// assert_eq!(parser.parse("1,2,3,"), Ok((vec!['1', '2', '3'], "")));
+/// assert_eq!(parser.parse("1,2 3"), Ok((vec!['1', '2'], " 3")));
+/// assert_eq!(parser.parse(""), Ok((vec![], "")));
+/// # }
+/// ```
+pub fn sep_end_by<F, P, S>(parser: P, separator: S) -> SepEndBy<F, P, S>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , S: Parser<Input=<P as Parser>::Input> {
+    SepEndBy { parser: parser, separator: separator, required: false, _marker: PhantomData }
+}
+
+///Parses `parser` one or more times separated and optionally ended by `separator`, returning a
+///collection with the values from `p`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = sep_end_by1(digit(), token(';'));
+/// assert_eq!(parser.parse("1;2;"), Ok((vec!['1', '2'], "")));
+/// assert!(parser.parse(";").is_err());
+/// # }
+/// ```
+pub fn sep_end_by1<F, P, S>(parser: P, separator: S) -> SepEndBy<F, P, S>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , S: Parser<Input=<P as Parser>::Input> {
+    SepEndBy { parser: parser, separator: separator, required: true, _marker: PhantomData }
+}
+
+impl_parser!{ EndBy(P, S) [F: FromIterator<<P as Parser>::Output>], Many<F, Skip<P, S>> }
+
+///Parses `parser` zero or more times where each value is followed by `separator`, returning a
+///collection with the values from `p`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = end_by(many1::<String, _>(letter()), token(';'));
+/// assert_eq!(parser.parse("a;bc;"), Ok((vec!["a".to_string(), "bc".to_string()], "")));
+/// //Every value must be followed by the separator
+/// assert!(parser.parse("a;bc").is_err());
+/// # }
+/// ```
+pub fn end_by<F, P, S>(parser: P, separator: S) -> EndBy<F, P, S>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , S: Parser<Input=<P as Parser>::Input> {
+    EndBy(many(parser.skip(separator)))
+}
+
+//Iterator which parses `parser` until `end` succeeds
+struct ManyTillIter<'a, P: 'a, E: 'a>
+    where P: Parser {
+    iter: Iter<&'a mut P>,
+    end: &'a mut E,
+    ended: bool
+}
+
+impl <'a, P, E> Iterator for ManyTillIter<'a, P, E>
+    where P: Parser
+        , E: Parser<Input=P::Input> {
+    type Item = P::Output;
+    fn next(&mut self) -> Option<P::Output> {
+        if self.ended || self.iter.error.is_some() {
+            return None
+        }
+        let end_error = match self.end.parse_lazy(self.iter.input.clone()) {
+            Ok((_, rest)) => {
+                self.iter.consumed = self.iter.consumed || !rest.is_empty();
+                self.iter.input = rest.into_inner();
+                self.ended = true;
+                return None
+            }
+            Err(Consumed::Empty(err)) => err,
+            Err(err) => {
+                self.iter.error = Some(err);
+                return None
+            }
+        };
+        let value = self.iter.next();
+        if let Some(Consumed::Empty(err)) = self.iter.error.take() {
+            self.iter.error = Some(Consumed::Empty(end_error.merge(err)));
+        }
+        value
+    }
+}
+
+#[derive(Clone)]
+pub struct ManyTill<F, P, E> {
+    parser: P,
+    end: E,
+    _marker: PhantomData<fn () -> F>
+}
+impl <F, P, E> Parser for ManyTill<F, P, E>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , E: Parser<Input=<P as Parser>::Input> {
+
+    type Input = <P as Parser>::Input;
+    type Output = F;
+    fn parse_lazy(&mut self, input: State<<P as Parser>::Input>) -> ParseResult<F, P::Input> {
+        let mut iter = ManyTillIter {
+            iter: Iter::new(&mut self.parser, input),
+            end: &mut self.end,
+            ended: false
+        };
+        let result = iter.by_ref().collect();
+        if iter.ended {
+            return iter.iter.into_result(result)
+        }
+        let mut err = match iter.iter.error {
+            Some(Consumed::Empty(err)) => err,
+            Some(err) => return Err(err),
+            //The collection stopped taking values before `end` was found
+            None => ParseError::empty(iter.iter.input.position.clone())
+        };
+        if !iter.iter.consumed {
+            return Err(Consumed::Empty(err))
+        }
+        //Input has been consumed so the error needs to be completed here instead of by
+        //the caller
+        match iter.iter.input.input.clone().uncons() {
+            Ok((t, _)) => err.add_error(Error::Unexpected(Info::Token(t))),
+            Err(error) => err.add_error(error)
+        }
+        iter.end.add_error(&mut err);
+        iter.iter.parser.add_error(&mut err);
+        Err(Consumed::Consumed(err))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.end.add_error(errors);
+        self.parser.add_error(errors)
+    }
+}
+
+///Parses `parser` zero or more times until `end` succeeds, returning a collection with the values
+///from `parser`. The value of `end` is ignored.
+///Use `skip_until` to skip input up to, but not including, `end` instead.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # fn main() {
+/// let mut comment = string("/*").with(many_till(any(), try(string("*/"))));
+/// assert_eq!(comment.parse("/* a * b */c"), Ok((" a * b ".to_string(), "c")));
+/// assert!(comment.parse("/* a").is_err());
+/// # }
+/// ```
+pub fn many_till<F, P, E>(parser: P, end: E) -> ManyTill<F, P, E>
+    where F: FromIterator<<P as Parser>::Output>
+        , P: Parser
+        , E: Parser<Input=<P as Parser>::Input> {
+    ManyTill { parser: parser, end: end, _marker: PhantomData }
+}
+
 
 impl <'a, I: Stream, O> Parser for FnMut(State<I>) -> ParseResult<O, I> + 'a {
     type Input = I;
@@ -581,8 +1065,20 @@ impl <P> Parser for Optional<P>
     fn parse_lazy(&mut self, input: State<<P as Parser>::Input>) -> ParseResult<Option<<P as Parser>::Output>, <P as Parser>::Input> {
         match self.0.parse_state(input.clone()) {
             Ok((x, rest)) => Ok((Some(x), rest)),
-            Err(err@Consumed::Consumed(_)) => return Err(err),
-            Err(Consumed::Empty(_)) => Ok((None, Consumed::Empty(input)))
+            Err(Consumed::Empty(_)) => Ok((None, Consumed::Empty(input))),
+            Err(err) => Err(err)
+        }
+    }
+}
+
+impl <P> Resumable for Optional<P>
+    where P: Resumable {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<P::Input>, state: &mut P::PartialState) -> ParseResult<Option<P::Output>, P::Input> {
+        match self.0.parse_resume_state(input.clone(), state) {
+            Ok((x, rest)) => Ok((Some(x), rest)),
+            Err(Consumed::Empty(_)) => Ok((None, Consumed::Empty(input))),
+            Err(err) => Err(err)
         }
     }
 }
@@ -647,8 +1143,8 @@ impl <I, O, P, Op> Parser for Chainl1<P, Op>
                     l = op(l, r);
                     input = if was_empty { rest } else { rest.as_consumed() };
                 }
-                Err(err@Consumed::Consumed(_)) => return Err(err),
-                Err(Consumed::Empty(_)) => break
+                Err(Consumed::Empty(_)) => break,
+                Err(err) => return Err(err)
             }
         }
         Ok((l, input))
@@ -698,8 +1194,8 @@ impl <I, O, P, Op> Parser for Chainr1<P, Op>
                     input = if was_empty { rest } else { rest.as_consumed() };
                     x
                 }
-                Err(err@Consumed::Consumed(_)) => return Err(err),
-                Err(Consumed::Empty(_)) => break
+                Err(Consumed::Empty(_)) => break,
+                Err(err) => return Err(err)
             };
             let was_empty = was_empty && input.is_empty();
             let rest = input.clone().into_inner();
@@ -708,8 +1204,8 @@ impl <I, O, P, Op> Parser for Chainr1<P, Op>
                     l = op(l, r);
                     input = if was_empty { rest } else { rest.as_consumed() };
                 }
-                Err(err@Consumed::Consumed(_)) => return Err(err),
-                Err(Consumed::Empty(_)) => break
+                Err(Consumed::Empty(_)) => break,
+                Err(err) => return Err(err)
             }
             
 
@@ -758,6 +1254,16 @@ impl <I, O, P> Parser for Try<P>
     }
 }
 
+impl <I, O, P> Resumable for Try<P>
+    where I: Stream
+        , P: Resumable<Input=I, Output=O> {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut P::PartialState) -> ParseResult<O, I> {
+        self.0.parse_resume_state(input, state)
+            .map_err(Consumed::as_empty)
+    }
+}
+
 ///Try acts as `p` except it acts as if the parser hadn't consumed any input
 ///if `p` returns an error after consuming input
 ///
@@ -779,22 +1285,356 @@ pub fn try<P>(p : P) -> Try<P>
 }
 
 #[derive(Clone)]
-pub struct And<P1, P2>(P1, P2);
-impl <I, A, B, P1, P2> Parser for And<P1, P2>
-    where I: Stream, P1: Parser<Input=I, Output=A>, P2: Parser<Input=I, Output=B> {
-
+pub struct Position<I>(PhantomData<fn (I) -> I>);
+impl <I> Parser for Position<I>
+    where I: Stream {
     type Input = I;
-    type Output = (A, B);
+    type Output = <I::Item as Positioner>::Position;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<Self::Output, I> {
+        Ok((input.position.clone(), Consumed::Empty(input)))
+    }
+}
+
+///Returns the current position of the input without consuming anything.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::combinator::position;
+/// # use pc::primitives::SourcePosition;
+/// # fn main() {
+/// let result = string("let")
+///     .with(position())
+///     .parse("let x")
+///     .map(|x| x.0);
+/// assert_eq!(result, Ok(SourcePosition { line: 1, column: 4, offset: 3, utf16_column: 4 }));
+/// # }
+/// ```
+pub fn position<I>() -> Position<I>
+    where I: Stream {
+    Position(PhantomData)
+}
+
+#[derive(Clone)]
+pub struct Spanned<P>(P);
+impl <I, P> Parser for Spanned<P>
+    where I: Stream
+        , P: Parser<Input=I> {
+    type Input = I;
+    type Output = (P::Output, Span<<I::Item as Positioner>::Position>);
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<Self::Output, I> {
+        let start = input.position.clone();
+        let (value, rest) = try!(self.0.parse_lazy(input));
+        let span = match rest {
+            Consumed::Consumed(ref rest) | Consumed::Empty(ref rest) | Consumed::Partial(ref rest) => {
+                Span { start: start, end: rest.position.clone() }
+            }
+        };
+        Ok(((value, span), rest))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.0.add_error(errors)
+    }
+}
+
+impl <I, P> Resumable for Spanned<P>
+    where I: Stream
+        , P: Resumable<Input=I> {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut P::PartialState) -> ParseResult<Self::Output, I> {
+        let start = input.position.clone();
+        let (value, rest) = try!(self.0.parse_resume(input, state));
+        let span = Span { start: start, end: rest.clone().into_inner().position };
+        Ok(((value, span), rest))
+    }
+}
+
+///Parses with `p` and returns its output together with the span of the input it consumed.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::combinator::spanned;
+/// # use pc::primitives::{BytePosition, Span, from_slice};
+/// # fn main() {
+/// let result = byte::byte(b'a')
+///     .with(spanned(many1::<Vec<_>, _>(byte::digit())))
+///     .parse(from_slice(&b"a123b"[..]))
+///     .map(|x| x.0);
+/// assert_eq!(result, Ok((b"123".to_vec(), Span {
+///     start: BytePosition { position: 1 },
+///     end: BytePosition { position: 4 }
+/// })));
+/// # }
+/// ```
+pub fn spanned<P>(p: P) -> Spanned<P>
+    where P: Parser {
+    Spanned(p)
+}
+
+#[derive(Clone)]
+pub struct GetState<I, U>(PhantomData<fn (I, U) -> (I, U)>);
+impl <I, U> Parser for GetState<I, U>
+    where I: Stream
+        , U: Clone {
+    type Input = StatefulStream<I, U>;
+    type Output = U;
+    fn parse_lazy(&mut self, input: State<StatefulStream<I, U>>) -> ParseResult<U, StatefulStream<I, U>> {
+        Ok((input.input.state().clone(), Consumed::Empty(input)))
+    }
+}
+
+///Returns a copy of the user state of a `StatefulStream` without consuming any input.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::combinator::{get_state, modify_state};
+/// # use pc::primitives::StatefulStream;
+/// # fn main() {
+/// //Counts the number of letters
+/// let letters = skip_many(letter().with(modify_state(|count: &mut i32| *count += 1)));
+/// let result = letters.with(get_state())
+///     .parse(StatefulStream::new("abc1", 0))
+///     .map(|x| x.0);
+/// assert_eq!(result, Ok(3));
+/// # }
+/// ```
+pub fn get_state<I, U>() -> GetState<I, U>
+    where I: Stream
+        , U: Clone {
+    GetState(PhantomData)
+}
+
+#[derive(Clone)]
+pub struct PutState<I, U>(U, PhantomData<fn (I) -> I>);
+impl <I, U> Parser for PutState<I, U>
+    where I: Stream
+        , U: Clone {
+    type Input = StatefulStream<I, U>;
+    type Output = ();
+    fn parse_lazy(&mut self, mut input: State<StatefulStream<I, U>>) -> ParseResult<(), StatefulStream<I, U>> {
+        *input.input.state_mut() = self.0.clone();
+        Ok(((), Consumed::Empty(input)))
+    }
+}
+
+///Replaces the user state of a `StatefulStream` with `state` without consuming any input.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::combinator::{get_state, put_state};
+/// # use pc::primitives::StatefulStream;
+/// # fn main() {
+/// let result = put_state("new").with(get_state())
+///     .parse(StatefulStream::new("", "old"))
+///     .map(|x| x.0);
+/// assert_eq!(result, Ok("new"));
+/// # }
+/// ```
+pub fn put_state<I, U>(state: U) -> PutState<I, U>
+    where I: Stream
+        , U: Clone {
+    PutState(state, PhantomData)
+}
+
+#[derive(Clone)]
+pub struct ModifyState<I, F>(F, PhantomData<fn (I) -> I>);
+impl <I, U, F> Parser for ModifyState<StatefulStream<I, U>, F>
+    where I: Stream
+        , U: Clone
+        , F: FnMut(&mut U) {
+    type Input = StatefulStream<I, U>;
+    type Output = ();
+    fn parse_lazy(&mut self, mut input: State<StatefulStream<I, U>>) -> ParseResult<(), StatefulStream<I, U>> {
+        (self.0)(input.input.state_mut());
+        Ok(((), Consumed::Empty(input)))
+    }
+}
+
+///Modifies the user state of a `StatefulStream` with `f` without consuming any input.
+///Modifications are undone if the parser backtracks to a point before `modify_state` was run.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::combinator::{get_state, modify_state};
+/// # use pc::primitives::StatefulStream;
+/// # fn main() {
+/// let mut parser = try(modify_state(|s: &mut Vec<char>| s.push('a')).with(char('a')).with(char('b')))
+///     .or(char('a').with(char('c')))
+///     .with(get_state());
+/// let result = parser.parse(StatefulStream::new("ac", vec![])).map(|x| x.0);
+/// assert_eq!(result, Ok(vec![]));
+/// # }
+/// ```
+pub fn modify_state<I, U, F>(f: F) -> ModifyState<StatefulStream<I, U>, F>
+    where I: Stream
+        , U: Clone
+        , F: FnMut(&mut U) {
+    ModifyState(f, PhantomData)
+}
+
+#[derive(Clone)]
+pub struct SkipUntil<P>(P);
+impl <I, P> Parser for SkipUntil<P>
+    where I: Stream
+        , P: Parser<Input=I> {
+
+    type Input = I;
+    type Output = ();
+    fn parse_lazy(&mut self, mut input: State<I>) -> ParseResult<(), I> {
+        let mut consumed = false;
+        loop {
+            match self.0.parse_lazy(input.clone()) {
+                Ok(_) => break,
+                Err(err@Consumed::Partial(_)) => return Err(err),
+                Err(_) => ()
+            }
+            match input.clone().uncons() {
+                Ok((_, rest)) => {
+                    consumed = true;
+                    input = rest.into_inner();
+                }
+                Err(err@Consumed::Partial(_)) => return Err(err),
+                Err(err) => {
+                    let err = err.into_inner();
+                    if err.errors.iter().any(|e| *e == Error::end_of_input()) {
+                        break
+                    }
+                    return Err(if consumed { Consumed::Consumed(err) } else { Consumed::Empty(err) })
+                }
+            }
+        }
+        Ok(((), if consumed { Consumed::Consumed(input) } else { Consumed::Empty(input) }))
+    }
+}
+
+///Skips input until `end` would succeed or the end of input is reached.
+///`end` itself is not consumed, making this parser suitable for skipping to a synchronization
+///point after an error.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::combinator::skip_until;
+/// # fn main() {
+/// let mut parser = skip_until(char(';'));
+/// assert_eq!(parser.parse("abc; def"), Ok(((), "; def")));
+/// assert_eq!(parser.parse("abc"), Ok(((), "")));
+/// # }
+/// ```
+pub fn skip_until<P>(end: P) -> SkipUntil<P>
+    where P: Parser {
+    SkipUntil(end)
+}
+
+#[derive(Clone)]
+pub struct RecoverWith<P, R>(P, R);
+impl <I, O, P, R> Parser for RecoverWith<P, R>
+    where I: Stream
+        , P: Parser<Input=RecoverStream<I>, Output=O>
+        , R: Parser<Input=RecoverStream<I>, Output=O> {
+
+    type Input = RecoverStream<I>;
+    type Output = O;
+    fn parse_state(&mut self, input: State<RecoverStream<I>>) -> ParseResult<O, RecoverStream<I>> {
+        let error = match self.0.parse_state(input.clone()) {
+            Ok(x) => return Ok(x),
+            Err(err@Consumed::Partial(_)) => return Err(err),
+            Err(error) => error
+        };
+        match self.1.parse_state(input.clone()) {
+            Ok((value, rest)) => {
+                let mut rest = rest.into_inner();
+                //Recovering without moving past the error would let a repeating parser loop forever
+                if rest.position == input.position {
+                    return Err(error)
+                }
+                let error = error.into_inner();
+                rest.input.push(ParseError::from_errors(error.position, error.errors));
+                Ok((value, Consumed::Consumed(rest)))
+            }
+            Err(err@Consumed::Partial(_)) => Err(err),
+            Err(_) => Err(error)
+        }
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.0.add_error(errors)
+    }
+}
+
+///Parses with `p` and if it fails, records the error in the `RecoverStream` and parses with
+///`recovery` from the same input instead, letting parsing continue after a syntax error.
+///`recovery` should skip past the erroneous input and return a placeholder value.
+///If `recovery` fails or does not move past the start of the input, the error from `p` is
+///returned and nothing is recorded.
+///
+///As the errors are recorded in the stream, errors recovered from inside a `try` or `or` which
+///is later backtracked out of are discarded.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::combinator::{recover_with, skip_until};
+/// # use pc::primitives::RecoverStream;
+/// # fn main() {
+/// let number = many1(digit()).map(|s: String| s.parse::<i32>().ok());
+/// let recovery = skip_until(char(';')).map(|_| None);
+/// let mut parser = many(recover_with(number, recovery).skip(char(';')));
+/// let result: Result<(Vec<Option<i32>>, _), _> = parser.parse(RecoverStream::new("1;x2;3;"));
+/// let (values, rest) = result.unwrap();
+/// assert_eq!(values, vec![Some(1), None, Some(3)]);
+/// let errors = rest.errors();
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].position.column, 3);
+/// # }
+/// ```
+pub fn recover_with<I, P, R>(p: P, recovery: R) -> RecoverWith<P, R>
+    where I: Stream
+        , P: Parser<Input=RecoverStream<I>>
+        , R: Parser<Input=RecoverStream<I>, Output=P::Output> {
+    RecoverWith(p, recovery)
+}
+
+#[derive(Clone)]
+pub struct And<P1, P2>(P1, P2);
+impl <I, A, B, P1, P2> Parser for And<P1, P2>
+    where I: Stream, P1: Parser<Input=I, Output=A>, P2: Parser<Input=I, Output=B> {
+
+    type Input = I;
+    type Output = (A, B);
     fn parse_lazy(&mut self, input: State<I>) -> ParseResult<(A, B), I> {
         let (a, rest) = try!(self.0.parse_lazy(input));
         rest.combine(move |rest| {
-            let (b, rest) = try!(self.1.parse_state(rest));
-            Ok(((a, b), rest))
+            let position = rest.position.clone();
+            match self.1.parse_state(rest) {
+                Ok((b, rest)) => Ok(((a, b), rest)),
+                Err(Consumed::Empty(mut err)) => {
+                    if err.position == position {
+                        self.0.add_trailing_error(&mut err);
+                    }
+                    Err(Consumed::Empty(err))
+                }
+                Err(err) => Err(err)
+            }
         })
     }
     fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
         self.0.add_error(errors)
     }
+    fn add_trailing_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.1.add_trailing_error(errors)
+    }
+}
+
+impl <I, P1, P2> Resumable for And<P1, P2>
+    where I: Stream, P1: Resumable<Input=I>, P2: Resumable<Input=I> {
+    type PartialState = <(P1, P2) as Resumable>::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut Self::PartialState) -> ParseResult<Self::Output, I> {
+        (&mut self.0, &mut self.1).parse_resume(input, state)
+    }
 }
 
 #[derive(Clone)]
@@ -811,6 +1651,18 @@ impl <I, P1, P2> Parser for With<P1, P2>
     fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
         self.0.add_error(errors)
     }
+    fn add_trailing_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.1.add_trailing_error(errors)
+    }
+}
+
+impl <I, P1, P2> Resumable for With<P1, P2>
+    where I: Stream, P1: Resumable<Input=I>, P2: Resumable<Input=I> {
+    type PartialState = <(P1, P2) as Resumable>::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut Self::PartialState) -> ParseResult<Self::Output, I> {
+        let ((_, b), rest) = try!((&mut self.0, &mut self.1).parse_resume(input, state));
+        Ok((b, rest))
+    }
 }
 
 #[derive(Clone)]
@@ -827,6 +1679,18 @@ impl <I, P1, P2> Parser for Skip<P1, P2>
     fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
         self.0.add_error(errors)
     }
+    fn add_trailing_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.1.add_trailing_error(errors)
+    }
+}
+
+impl <I, P1, P2> Resumable for Skip<P1, P2>
+    where I: Stream, P1: Resumable<Input=I>, P2: Resumable<Input=I> {
+    type PartialState = <(P1, P2) as Resumable>::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut Self::PartialState) -> ParseResult<Self::Output, I> {
+        let ((a, _), rest) = try!((&mut self.0, &mut self.1).parse_resume(input, state));
+        Ok((a, rest))
+    }
 }
 
 #[derive(Clone)]
@@ -846,6 +1710,14 @@ impl <I, P> Parser for Message<P>
     }
 }
 
+impl <I, P> Resumable for Message<P>
+    where I: Stream, P: Resumable<Input=I> {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut P::PartialState) -> ParseResult<Self::Output, I> {
+        self.0.parse_resume(input, state)
+    }
+}
+
 #[derive(Clone)]
 pub struct Or<P1, P2>(P1, P2) where P1: Parser, P2: Parser;
 impl <I, O, P1, P2> Parser for Or<P1, P2>
@@ -856,14 +1728,14 @@ impl <I, O, P1, P2> Parser for Or<P1, P2>
     fn parse_lazy(&mut self, input: State<I>) -> ParseResult<O, I> {
         match self.0.parse_lazy(input.clone()) {
             Ok(x) => Ok(x),
-            Err(err@Consumed::Consumed(_)) => Err(err),
             Err(Consumed::Empty(error1)) => {
                 match self.1.parse_lazy(input) {
                     Ok(x) => Ok(x),
-                    Err(err@Consumed::Consumed(_)) => Err(err),
-                    Err(Consumed::Empty(error2)) => Err(Consumed::Empty(error1.merge(error2)))
+                    Err(Consumed::Empty(error2)) => Err(Consumed::Empty(error1.merge(error2))),
+                    Err(err) => Err(err)
                 }
             }
+            Err(err) => Err(err)
         }
     }
     fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
@@ -872,6 +1744,32 @@ impl <I, O, P1, P2> Parser for Or<P1, P2>
     }
 }
 
+impl <I, O, P1, P2> Resumable for Or<P1, P2>
+    where I: Stream, P1: Resumable<Input=I, Output=O>, P2: Resumable<Input=I, Output=O> {
+    //The error of the first parser is stored once it has failed and the second parser is running
+    type PartialState = (P1::PartialState, Option<ParseError<I>>, P2::PartialState);
+    fn parse_resume(&mut self, input: State<I>, state: &mut Self::PartialState) -> ParseResult<O, I> {
+        let (ref mut first, ref mut error, ref mut second) = *state;
+        let error1 = match error.take() {
+            Some(error1) => error1,
+            None => {
+                match self.0.parse_resume(input.clone(), first) {
+                    Err(Consumed::Empty(error1)) => error1,
+                    result => return result
+                }
+            }
+        };
+        match self.1.parse_resume(input, second) {
+            Err(Consumed::Empty(error2)) => Err(Consumed::Empty(error1.merge(error2))),
+            Err(err@Consumed::Partial(_)) => {
+                *error = Some(error1);
+                Err(err)
+            }
+            result => result
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Map<P, F>(P, F);
 impl <I, A, B, P, F> Parser for Map<P, F>
@@ -888,6 +1786,20 @@ impl <I, A, B, P, F> Parser for Map<P, F>
     fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
         self.0.add_error(errors);
     }
+    fn add_trailing_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.0.add_trailing_error(errors);
+    }
+}
+
+impl <I, A, B, P, F> Resumable for Map<P, F>
+    where I: Stream, P: Resumable<Input=I, Output=A>, F: FnMut(A) -> B {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut P::PartialState) -> ParseResult<B, I> {
+        match self.0.parse_resume(input, state) {
+            Ok((x, input)) => Ok(((self.1)(x), input)),
+            Err(err) => Err(err)
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -911,6 +1823,31 @@ impl <P, N, F> Parser for Then<P, F>
     }
 }
 
+impl <P, N, F> Resumable for Then<P, F>
+    where F: FnMut(<P as Parser>::Output) -> N
+        , P: Resumable
+        , N: Resumable<Input=<P as Parser>::Input> {
+    //Once `p` has succeeded the parser it returned is stored along with the state after `p`
+    type PartialState = (P::PartialState, Option<(N, Consumed<State<N::Input>>)>, N::PartialState);
+    fn parse_resume(&mut self, input: State<N::Input>, state: &mut Self::PartialState) -> ParseResult<N::Output, N::Input> {
+        let (ref mut first, ref mut next, ref mut second) = *state;
+        let (mut parser, rest) = match next.take() {
+            Some(next) => next,
+            None => {
+                let (value, rest) = try!(self.0.parse_resume(input, first));
+                ((self.1)(value), rest)
+            }
+        };
+        match rest.clone().combine(|input| parser.parse_resume_state(input, second)) {
+            Err(err@Consumed::Partial(_)) => {
+                *next = Some((parser, rest));
+                Err(err)
+            }
+            result => result
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Expected<P>(P, Info<<P::Input as Stream>::Item, <P::Input as Stream>::Range>)
     where P: Parser;
@@ -941,6 +1878,14 @@ impl <P> Parser for Expected<P>
     }
 }
 
+impl <P> Resumable for Expected<P>
+    where P: Resumable {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<P::Input>, state: &mut P::PartialState) -> ParseResult<P::Output, P::Input> {
+        self.0.parse_resume(input, state)
+    }
+}
+
 #[derive(Clone)]
 pub struct AndThen<P, F>(P, F);
 impl <P, F, O, E> Parser for AndThen<P, F>
@@ -964,6 +1909,28 @@ impl <P, F, O, E> Parser for AndThen<P, F>
     }
 }
 
+impl <P, F, O, E> Resumable for AndThen<P, F>
+    where P: Resumable
+        , F: FnMut(P::Output) -> Result<O, E>
+        , E: Into<Error<<P::Input as Stream>::Item, <P::Input as Stream>::Range>> {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<P::Input>, state: &mut P::PartialState) -> ParseResult<O, P::Input> {
+        self.0.parse_resume(input, state)
+            .and_then(|(o, input)|
+                match (self.1)(o) {
+                    Ok(o) => Ok((o, input)),
+                    Err(err) => Err(input.map(move |input| ParseError::new(input.position, err.into())))
+                }
+            )
+    }
+}
+
+impl_restart!(Any<I>, Satisfy<I, P>, Token<I> [I: Stream, I::Item: PartialEq], Choice<S, P>,
+              Unexpected<I> [I: Stream], Value<I, T>, CountMinMax<F, P>, SepBy<F, P, S>, SepBy1<F, P, S>,
+              SepEndBy<F, P, S> [P: Parser, S: Parser<Input=<P as Parser>::Input>],
+              ManyTill<F, P, E>, FnParser<I, F>, Chainl1<P, Op>, Chainr1<P, Op>, Position<I>, GetState<I, U>,
+              PutState<I, U>, ModifyState<I, F>, SkipUntil<P>, RecoverWith<P, R>);
+
 ///Extension trait which provides functions that are more conveniently used through method calls
 pub trait ParserExt : Parser + Sized {
 
@@ -1150,6 +2117,43 @@ pub trait ParserExt : Parser + Sized {
         AndThen(self, f)
     }
 
+    ///Parses with `self` and returns its output together with the span of the input it consumed.
+    ///Equivalent to `spanned(self)`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::primitives::{SourcePosition, Span};
+    /// # fn main() {
+    /// let result = spaces()
+    ///     .with(many1::<String, _>(letter()).with_span())
+    ///     .parse("\n  hello")
+    ///     .map(|x| x.0);
+    /// assert_eq!(result, Ok(("hello".to_string(), Span {
+    ///     start: SourcePosition { line: 2, column: 3, offset: 3, utf16_column: 3 },
+    ///     end: SourcePosition { line: 2, column: 8, offset: 8, utf16_column: 8 }
+    /// })));
+    /// # }
+    /// ```
+    fn with_span(self) -> Spanned<Self> {
+        Spanned(self)
+    }
+
+    ///Traces the name of `self`, the position it starts at and its outcome each time it is run.
+    ///Equivalent to `trace(name, self)`, see the `trace` module for details.
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # fn main() {
+    /// let mut parser = (digit().debug("first"), digit().debug("second"));
+    /// assert_eq!(parser.parse("12").map(|x| x.0), Ok(('1', '2')));
+    /// # }
+    /// ```
+    fn debug(self, name: &'static str) -> Trace<Self> {
+        trace(name, self)
+    }
+
     ///Creates an iterator from a parser and a state. Can be used as an alternative to `many` when
     ///collecting directly into a `FromIterator` type is not desirable
     ///
@@ -1208,6 +2212,61 @@ tuple_parser!(A, B, C, D, E, F, G, H, I, J);
 tuple_parser!(A, B, C, D, E, F, G, H, I, J, K);
 tuple_parser!(A, B, C, D, E, F, G, H, I, J, K, L);
 
+//The state of a suspended tuple holds the state after the last parser which succeeded along with
+//the value and progress of each parser
+macro_rules! tuple_resumable {
+    (($h: ident, $h_state: ident), $(($id: ident, $id_state: ident)),+) => {
+        impl <Input: Stream, $h: Resumable<Input=Input>, $($id: Resumable<Input=Input>),+> Resumable for ($h, $($id),+) {
+            type PartialState = (Option<Consumed<State<Input>>>,
+                                 ((Option<$h::Output>, $h::PartialState), $((Option<$id::Output>, $id::PartialState)),+));
+            #[allow(non_snake_case)]
+            fn parse_resume(&mut self, input: State<Input>, state: &mut Self::PartialState) -> ParseResult<Self::Output, Input> {
+                let (ref mut $h, $(ref mut $id),+) = *self;
+                let (ref mut rest, (ref mut $h_state, $(ref mut $id_state),+)) = *state;
+                let mut input = match rest.take() {
+                    Some(input) => input,
+                    None => {
+                        let (value, input) = try!($h.parse_resume(input, &mut $h_state.1));
+                        $h_state.0 = Some(value);
+                        input
+                    }
+                };
+                $(
+                if $id_state.0.is_none() {
+                    match input.clone().combine(|input| $id.parse_resume_state(input, &mut $id_state.1)) {
+                        Ok((value, next)) => {
+                            $id_state.0 = Some(value);
+                            input = next;
+                        }
+                        Err(err@Consumed::Partial(_)) => {
+                            *rest = Some(input);
+                            return Err(err)
+                        }
+                        Err(err) => {
+                            *state = Default::default();
+                            return Err(err)
+                        }
+                    }
+                }
+                )+
+                Ok((($h_state.0.take().unwrap(), $($id_state.0.take().unwrap()),+), input))
+            }
+        }
+    }
+}
+
+tuple_resumable!((A, a), (B, b));
+tuple_resumable!((A, a), (B, b), (C, c));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h), (I, i));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h), (I, i), (J, j));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h), (I, i), (J, j), (K, k));
+tuple_resumable!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h), (I, i), (J, j), (K, k), (L, l));
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1258,4 +2317,190 @@ mod tests {
                 Error::Expected("digit".into())]
         }));
     }
+
+    #[test]
+    fn spanned_position() {
+        use primitives::{SourcePosition, Span};
+        use char::{char, spaces};
+        let mut parser = (position(), spaces().with(letter().with_span()), many(char(' ').or(char('\n'))).map(|_: String| ()), position());
+        let result = parser.parse(" \n a\n ").map(|x| x.0);
+        let a = SourcePosition { line: 2, column: 2, offset: 3, utf16_column: 2 };
+        let b = SourcePosition { line: 2, column: 3, offset: 4, utf16_column: 3 };
+        assert_eq!(result, Ok((SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 },
+                               ('a', Span { start: a, end: b }),
+                               (),
+                               SourcePosition { line: 3, column: 2, offset: 6, utf16_column: 2 })));
+    }
+
+    #[test]
+    fn spanned_empty_and_error() {
+        use primitives::Span;
+        let start = char::start();
+        assert_eq!(spanned(value(1)).parse("a").map(|x| x.0), Ok((1, Span { start: start, end: start })));
+        assert_eq!(spanned(digit()).parse("a"), Err(ParseError {
+            position: start,
+            errors: vec![Error::Unexpected('a'.into()), Error::Expected("digit".into())]
+        }));
+    }
+
+    #[test]
+    fn count_error() {
+        use primitives::SourcePosition;
+        let result = count::<String, _>(3, digit()).parse("12a");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 3, offset: 2, utf16_column: 3 },
+            errors: vec![Error::Unexpected('a'.into()),
+                         Error::Expected("digit".into()),
+                         Error::Message("Expected 3 repetitions but found 2".into())]
+        }));
+        let result = count_min_max::<String, _>(1, 2, digit()).parse("a");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 },
+            errors: vec![Error::Message("Expected at least 1 repetition but found 0".into()),
+                         Error::Unexpected('a'.into()),
+                         Error::Expected("digit".into())]
+        }));
+    }
+
+    #[test]
+    fn count_collection_stops_early() {
+        use primitives::SourcePosition;
+        use combinator::optional;
+        let result = count::<Option<Vec<_>>, _>(3, optional(digit())).parse("1a");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 2, offset: 1, utf16_column: 2 },
+            errors: vec![Error::Message("Expected 3 repetitions but found 2".into())]
+        }));
+        let result = count::<Option<Vec<_>>, _>(2, optional(digit())).parse("12a");
+        assert_eq!(result, Ok((Some(vec!['1', '2']), "a")));
+    }
+
+    #[test]
+    fn count_stops_at_max() {
+        assert_eq!(count::<Vec<_>, _>(0, digit()).parse("1"), Ok((vec![], "1")));
+        assert_eq!(count_min_max::<String, _>(0, 2, digit()).parse("123"), Ok(("12".to_string(), "3")));
+        assert_eq!(skip_count_min_max(1, 2, digit()).parse("1a"), Ok(((), "a")));
+        //Errors which consumed input are returned even if enough repetitions were found
+        let result = count_min_max::<Vec<_>, _>(1, 3, (digit(), letter())).parse("1a2");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn sep_end_by_trailing_separator() {
+        let mut parser = sep_end_by::<Vec<_>, _, _>(digit(), token(','));
+        assert_eq!(parser.parse("1,2,"), Ok((vec!['1', '2'], "")));
+        assert_eq!(parser.parse(",1"), Ok((vec![], ",1")));
+        //A separator which fails after consuming input is an error
+        let mut parser = sep_end_by::<Vec<_>, _, _>(digit(), (token(','), token(' ')));
+        assert!(parser.parse("1,2,").is_err());
+        let result = sep_end_by1::<Vec<_>, _, _>(digit(), token(',')).parse("a");
+        assert_eq!(result.map_err(|err| err.errors), Err(vec![
+            Error::Unexpected('a'.into()),
+            Error::Expected("digit".into())
+        ]));
+    }
+
+    #[test]
+    fn sep_by1_errors() {
+        let result = sep_by1::<Vec<_>, _, _>(digit(), token(',')).parse("");
+        assert_eq!(result.map_err(|err| err.errors), Err(vec![
+            Error::end_of_input(),
+            Error::Expected("digit".into())
+        ]));
+        //A trailing separator is not allowed
+        assert!(sep_by1::<Vec<_>, _, _>(digit(), token(',')).parse("1,").is_err());
+    }
+
+    #[test]
+    fn many_till_errors() {
+        use primitives::SourcePosition;
+        let result = many_till::<String, _, _>(letter(), token(';')).parse("ab1");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 3, offset: 2, utf16_column: 3 },
+            errors: vec![Error::Unexpected('1'.into()),
+                         Error::Expected(';'.into()),
+                         Error::Expected("letter".into())]
+        }));
+        let result = many_till::<String, _, _>(letter(), token(';')).parse("1");
+        assert_eq!(result.map_err(|err| err.errors), Err(vec![
+            Error::Unexpected('1'.into()),
+            Error::Expected(';'.into()),
+            Error::Expected("letter".into())
+        ]));
+        assert_eq!(many_till::<String, _, _>(letter(), token(';')).parse(";a"), Ok((String::new(), "a")));
+    }
+
+    #[test]
+    fn many_till_collection_stops_early() {
+        use primitives::SourcePosition;
+        let result = many_till::<Option<Vec<_>>, _, _>(optional(digit()), token(';')).parse("1a;");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 2, offset: 1, utf16_column: 2 },
+            errors: vec![Error::Unexpected('a'.into()),
+                         Error::Expected(';'.into())]
+        }));
+        let result = many_till::<Option<Vec<_>>, _, _>(optional(digit()), token(';')).parse("12;");
+        assert_eq!(result, Ok((Some(vec!['1', '2']), "")));
+    }
+
+    #[test]
+    fn skip_until_end_of_input() {
+        let mut parser = skip_until(token(';'));
+        assert_eq!(parser.parse(""), Ok(((), "")));
+        assert_eq!(parser.parse(";"), Ok(((), ";")));
+        assert_eq!(parser.parse("ab"), Ok(((), "")));
+    }
+
+    #[test]
+    fn recover_with_collects_sorted_errors() {
+        use primitives::SourcePosition;
+        use char::{char, spaces};
+        let stmt = (letter(), token('='), many1(digit()))
+            .map(|(name, _, value): (char, char, String)| Some((name, value)));
+        let recovery = skip_until(char(';')).map(|_| None);
+        let mut parser = many(recover_with(stmt, recovery).skip(char(';')).skip(spaces()));
+        let result: Result<(Vec<_>, _), _> = parser.parse(RecoverStream::new("a=1; b=; 1=2;\nc=3;"));
+        let (values, rest) = result.unwrap();
+        assert_eq!(values, vec![
+            Some(('a', "1".to_string())),
+            None,
+            None,
+            Some(('c', "3".to_string()))
+        ]);
+        let errors = rest.errors();
+        assert_eq!(errors.iter().map(|err| err.position).collect::<Vec<_>>(), vec![
+            SourcePosition { line: 1, column: 8, offset: 7, utf16_column: 8 },
+            SourcePosition { line: 1, column: 10, offset: 9, utf16_column: 10 }
+        ]);
+        assert_eq!(errors[1].errors, vec![Error::Unexpected('1'.into()), Error::Expected("letter".into())]);
+        assert_eq!(rest.into_inner(), "");
+    }
+
+    #[test]
+    fn recover_with_empty_error_and_empty_recovery() {
+        let mut parser = recover_with(digit(), skip_until(token(';')).map(|_| '0'));
+        assert!(parser.parse(RecoverStream::new(";")).is_err());
+        let (value, rest) = parser.parse(RecoverStream::new("a;")).unwrap();
+        assert_eq!(value, '0');
+        assert_eq!(rest.errors().len(), 1);
+        assert_eq!(rest.into_inner(), ";");
+    }
+
+    #[test]
+    fn recover_with_without_progress_fails() {
+        use char::char;
+        let mut parser = many::<Vec<_>, _>(recover_with((char('a'), char('b')).map(|_| 'x'), value('?')));
+        assert!(parser.parse(RecoverStream::new("ac")).is_err());
+    }
+
+    #[test]
+    fn recover_with_errors_are_backtracked() {
+        use char::{char, string};
+        let recovered = recover_with(digit(), skip_until(token(';')).map(|_| '0'));
+        let mut parser = try(recovered.skip(string(";!")))
+            .or(letter().skip(char(';')));
+        let (value, rest) = parser.parse(RecoverStream::new("a;")).unwrap();
+        assert_eq!(value, 'a');
+        assert!(rest.errors().is_empty());
+    }
 }
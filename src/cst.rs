@@ -0,0 +1,463 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use primitives::{Error, Parser, ParseError, ParseResult, RangeStream, SourcePosition, State, Stream};
+use memo::MemoStream;
+
+///A node in a concrete syntax tree, holding its kind and every child in the order they appeared in
+///the source.
+///Like the green nodes of a green tree a node does not store its position, only its text.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Node<K> {
+    ///The kind of the node as given to `node`
+    pub kind: K,
+    ///The child nodes and leaves of the node
+    pub children: Vec<Element<K>>
+}
+
+///A token in a concrete syntax tree together with the exact text it was parsed from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Leaf<K> {
+    ///The kind of the leaf as given to `leaf`, or the trivia kind given to `CstStream::new`
+    pub kind: K,
+    ///The text of the leaf
+    pub text: String
+}
+
+///An element of a concrete syntax tree
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Element<K> {
+    Node(Node<K>),
+    Leaf(Leaf<K>)
+}
+
+impl <K> Node<K> {
+    ///Returns the length in bytes of the text of the node
+    pub fn text_len(&self) -> usize {
+        self.children.iter().map(|child| child.text_len()).sum()
+    }
+}
+
+impl <K> Element<K> {
+    ///Returns the kind of the element
+    pub fn kind(&self) -> &K {
+        match *self {
+            Element::Node(ref node) => &node.kind,
+            Element::Leaf(ref leaf) => &leaf.kind
+        }
+    }
+
+    ///Returns the length in bytes of the text of the element
+    pub fn text_len(&self) -> usize {
+        match *self {
+            Element::Node(ref node) => node.text_len(),
+            Element::Leaf(ref leaf) => leaf.text.len()
+        }
+    }
+}
+
+///Writes the text the node was parsed from
+impl <K> fmt::Display for Node<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for child in &self.children {
+            try!(child.fmt(f));
+        }
+        Ok(())
+    }
+}
+
+///Writes the text the element was parsed from
+impl <K> fmt::Display for Element<K> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Element::Node(ref node) => node.fmt(f),
+            Element::Leaf(ref leaf) => f.write_str(&leaf.text)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Event<K> {
+    Start(K, usize),
+    Leaf(K, usize, usize),
+    Finish(usize)
+}
+
+struct Builder<I, K> {
+    source: I,
+    trivia: K,
+    events: Vec<Event<K>>
+}
+
+///Wrapper around a character stream which records the nodes and leaves parsed by `node` and
+///`leaf` so that a concrete syntax tree can be built with `tree` after parsing.
+///
+///Events recorded by parsers which are later backtracked over are discarded so the tree only
+///contains what was parsed on the path which led to the stream.
+pub struct CstStream<I, K> {
+    input: I,
+    builder: Rc<RefCell<Builder<I, K>>>,
+    //The number of events recorded on the path to this stream
+    events: usize,
+    //The byte offset of this stream from the start of the source
+    offset: usize
+}
+
+impl <I, K> Clone for CstStream<I, K>
+    where I: Clone {
+    fn clone(&self) -> CstStream<I, K> {
+        CstStream {
+            input: self.input.clone(),
+            builder: self.builder.clone(),
+            events: self.events,
+            offset: self.offset
+        }
+    }
+}
+
+impl <I, K> fmt::Debug for CstStream<I, K>
+    where I: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CstStream {{ input: {:?}, events: {}, offset: {} }}", self.input, self.events, self.offset)
+    }
+}
+
+impl <I, K> CstStream<I, K>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str>
+        , K: Clone {
+    ///Wraps `input` so that the parsers in this module can record a tree while parsing it.
+    ///Any text which is consumed without being part of a leaf is added to the tree as leaves of
+    ///the `trivia` kind so that no text is lost.
+    ///`uncons_range` of `input` must count in bytes, as it does for `&str`.
+    pub fn new(input: I, trivia: K) -> CstStream<I, K> {
+        let builder = Builder { source: input.clone(), trivia: trivia, events: Vec::new() };
+        CstStream { input: input, builder: Rc::new(RefCell::new(builder)), events: 0, offset: 0 }
+    }
+
+    ///Returns the byte offset of the stream from the start of the source
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    ///Returns the wrapped stream
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn record(&mut self, events: usize, event: Event<K>) {
+        let mut builder = self.builder.borrow_mut();
+        builder.events.truncate(events);
+        builder.events.push(event);
+        self.events = builder.events.len();
+    }
+
+    ///Builds the tree of everything which has been consumed up to this stream.
+    ///Concatenating the text of the returned elements gives back the consumed source exactly.
+    ///Returns the error of the wrapped stream if it could not return the consumed source with
+    ///`uncons_range`.
+    pub fn tree(&self) -> Result<Vec<Element<K>>, Error<char, I::Range>> {
+        let builder = self.builder.borrow();
+        let (source, _) = try!(builder.source.clone().uncons_range(self.offset));
+        let source = source.as_ref();
+        let mut tree = TreeBuilder {
+            source: source,
+            trivia: &builder.trivia,
+            cursor: 0,
+            stack: Vec::new(),
+            elements: Vec::new()
+        };
+        for event in &builder.events[..self.events] {
+            match *event {
+                Event::Start(ref kind, offset) => {
+                    tree.gap(offset);
+                    tree.stack.push(Node { kind: kind.clone(), children: Vec::new() });
+                }
+                Event::Leaf(ref kind, start, end) => {
+                    tree.gap(start);
+                    let leaf = Leaf { kind: kind.clone(), text: source[start..end].to_string() };
+                    tree.push(Element::Leaf(leaf));
+                    tree.cursor = end;
+                }
+                Event::Finish(offset) => {
+                    tree.gap(offset);
+                    tree.finish();
+                }
+            }
+        }
+        tree.gap(self.offset);
+        while !tree.stack.is_empty() {
+            tree.finish();
+        }
+        Ok(tree.elements)
+    }
+}
+
+struct TreeBuilder<'a, K: 'a> {
+    source: &'a str,
+    trivia: &'a K,
+    cursor: usize,
+    stack: Vec<Node<K>>,
+    elements: Vec<Element<K>>
+}
+
+impl <'a, K> TreeBuilder<'a, K>
+    where K: Clone {
+    fn push(&mut self, element: Element<K>) {
+        match self.stack.last_mut() {
+            Some(node) => node.children.push(element),
+            None => self.elements.push(element)
+        }
+    }
+
+    //Adds any text which is not part of a leaf as trivia
+    fn gap(&mut self, offset: usize) {
+        if offset > self.cursor {
+            let text = self.source[self.cursor..offset].to_string();
+            self.push(Element::Leaf(Leaf { kind: self.trivia.clone(), text: text }));
+            self.cursor = offset;
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(node) = self.stack.pop() {
+            self.push(Element::Node(node));
+        }
+    }
+}
+
+impl <I, K> Stream for CstStream<I, K>
+    where I: Stream<Item=char>
+        , I::Range: AsRef<str> {
+    type Item = char;
+    type Range = I::Range;
+    fn uncons(self) -> Result<(char, CstStream<I, K>), Error<char, I::Range>> {
+        let (c, input) = try!(self.input.uncons());
+        Ok((c, CstStream {
+            input: input,
+            builder: self.builder,
+            events: self.events,
+            offset: self.offset + c.len_utf8()
+        }))
+    }
+    fn update_position(&self, item: &char, position: &mut SourcePosition) {
+        self.input.update_position(item, position)
+    }
+    fn update_range_position(&self, range: &I::Range, position: &mut SourcePosition) {
+        self.input.update_range_position(range, position)
+    }
+    fn is_partial(&self) -> bool {
+        self.input.is_partial()
+    }
+    fn start_position(&self) -> SourcePosition {
+        self.input.start_position()
+    }
+}
+
+impl <I, K> MemoStream for CstStream<I, K>
+    where I: MemoStream<Item=char>
+        , I::Range: AsRef<str> {
+    type Key = I::Key;
+    fn memo_key(&self) -> I::Key {
+        self.input.memo_key()
+    }
+}
+
+impl <I, K> RangeStream for CstStream<I, K>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str> {
+    fn uncons_range(self, size: usize) -> Result<(I::Range, CstStream<I, K>), Error<char, I::Range>> {
+        let (range, input) = try!(self.input.uncons_range(size));
+        let offset = self.offset + range.as_ref().len();
+        Ok((range, CstStream { input: input, builder: self.builder, events: self.events, offset: offset }))
+    }
+    fn uncons_while<F>(self, predicate: F) -> Result<(I::Range, CstStream<I, K>), Error<char, I::Range>>
+        where F: FnMut(char) -> bool {
+        let (range, input) = try!(self.input.uncons_while(predicate));
+        let offset = self.offset + range.as_ref().len();
+        Ok((range, CstStream { input: input, builder: self.builder, events: self.events, offset: offset }))
+    }
+}
+
+#[derive(Clone)]
+pub struct NodeParser<K, P>(K, P);
+impl <I, K, P> Parser for NodeParser<K, P>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str>
+        , K: Clone
+        , P: Parser<Input=CstStream<I, K>> {
+    type Input = CstStream<I, K>;
+    type Output = P::Output;
+    fn parse_lazy(&mut self, mut input: State<CstStream<I, K>>) -> ParseResult<P::Output, CstStream<I, K>> {
+        let events = input.input.events;
+        let offset = input.input.offset;
+        input.input.record(events, Event::Start(self.0.clone(), offset));
+        let (value, rest) = try!(self.1.parse_lazy(input));
+        Ok((value, rest.map(|mut rest| {
+            let events = rest.input.events;
+            let offset = rest.input.offset;
+            rest.input.record(events, Event::Finish(offset));
+            rest
+        })))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.1.add_error(errors)
+    }
+}
+
+///Parses with `p`, recording a node of `kind` containing everything `p` consumed.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::cst::{leaf, node, CstStream, Element, Leaf, Node};
+/// # fn main() {
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Kind { Sum, Number, Plus, Whitespace }
+/// let number = || leaf(Kind::Number, many1::<String, _>(digit()));
+/// let mut sum = node(Kind::Sum, (number(), spaces(), leaf(Kind::Plus, char('+')), spaces(), number()));
+///
+/// let (_, rest) = sum.parse(CstStream::new("1 +  2", Kind::Whitespace)).unwrap();
+/// let leaf = |kind, text: &str| Element::Leaf(Leaf { kind: kind, text: text.to_string() });
+/// assert_eq!(rest.tree().unwrap(), vec![Element::Node(Node {
+///     kind: Kind::Sum,
+///     children: vec![
+///         leaf(Kind::Number, "1"),
+///         leaf(Kind::Whitespace, " "),
+///         leaf(Kind::Plus, "+"),
+///         leaf(Kind::Whitespace, "  "),
+///         leaf(Kind::Number, "2"),
+///     ]
+/// })]);
+/// assert_eq!(rest.tree().unwrap()[0].to_string(), "1 +  2");
+/// # }
+/// ```
+pub fn node<I, K, P>(kind: K, p: P) -> NodeParser<K, P>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str>
+        , K: Clone
+        , P: Parser<Input=CstStream<I, K>> {
+    NodeParser(kind, p)
+}
+
+#[derive(Clone)]
+pub struct LeafParser<K, P>(K, P);
+impl <I, K, P> Parser for LeafParser<K, P>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str>
+        , K: Clone
+        , P: Parser<Input=CstStream<I, K>> {
+    type Input = CstStream<I, K>;
+    type Output = P::Output;
+    fn parse_lazy(&mut self, input: State<CstStream<I, K>>) -> ParseResult<P::Output, CstStream<I, K>> {
+        let events = input.input.events;
+        let start = input.input.offset;
+        let (value, rest) = try!(self.1.parse_lazy(input));
+        Ok((value, rest.map(|mut rest| {
+            //Anything recorded by `p` is replaced by the leaf
+            let end = rest.input.offset;
+            rest.input.record(events, Event::Leaf(self.0.clone(), start, end));
+            rest
+        })))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.1.add_error(errors)
+    }
+}
+
+///Parses with `p`, recording the text `p` consumed as a leaf of `kind`.
+///Any nodes or leaves recorded by `p` itself are discarded.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::cst::{leaf, CstStream};
+/// # fn main() {
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Kind { Comment, Word, Whitespace }
+/// let comment = leaf(Kind::Comment, (string("//"), skip_many(satisfy(|c| c != '\n'))));
+/// let mut parser = many::<Vec<_>, _>(leaf(Kind::Word, many1::<String, _>(letter())).skip(spaces()))
+///     .skip(comment);
+/// let (_, rest) = parser.parse(CstStream::new("a  b // c", Kind::Whitespace)).unwrap();
+/// let tree = rest.tree().unwrap();
+/// let kinds: Vec<_> = tree.iter().map(|element| element.kind().clone()).collect();
+/// assert_eq!(kinds, [Kind::Word, Kind::Whitespace, Kind::Word, Kind::Whitespace, Kind::Comment]);
+/// let text: String = tree.iter().map(|element| element.to_string()).collect();
+/// assert_eq!(text, "a  b // c");
+/// # }
+/// ```
+pub fn leaf<I, K, P>(kind: K, p: P) -> LeafParser<K, P>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str>
+        , K: Clone
+        , P: Parser<Input=CstStream<I, K>> {
+    LeafParser(kind, p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::Parser;
+    use combinator::{many, many1, optional, try, ParserExt};
+    use char::{char, digit, letter, spaces};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Kind {
+        Call,
+        Ident,
+        Number,
+        Punct,
+        Trivia
+    }
+
+    fn leaf_element(kind: Kind, text: &str) -> Element<Kind> {
+        Element::Leaf(Leaf { kind: kind, text: text.to_string() })
+    }
+
+    #[test]
+    fn backtracked_events_are_discarded() {
+        let ident = || leaf(Kind::Ident, many1::<String, _>(letter()));
+        let call = node(Kind::Call, (ident(), leaf(Kind::Punct, char('(')), ident(), leaf(Kind::Punct, char(')'))));
+        let mut parser = try(call).map(|_| ()).or(ident().map(|_| ()));
+        let (_, rest) = parser.parse(CstStream::new("f(x", Kind::Trivia)).unwrap();
+        assert_eq!(rest.tree().unwrap(), vec![leaf_element(Kind::Ident, "f")]);
+    }
+
+    #[test]
+    fn lossless_nested_nodes() {
+        let number = || leaf(Kind::Number, many1::<String, _>(digit())).skip(spaces());
+        let punct = |c| leaf(Kind::Punct, char(c)).skip(spaces());
+        let mut parser = spaces().with(node(Kind::Call, (
+            leaf(Kind::Ident, many1::<String, _>(letter())),
+            punct('('),
+            many::<Vec<_>, _>(number().skip(optional(punct(',')))),
+            punct(')')
+        )));
+        let input = " \tf( 1 ,2,\n3 )  ";
+        let (_, rest) = parser.parse(CstStream::new(input, Kind::Trivia)).unwrap();
+        let tree = rest.tree().unwrap();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0], leaf_element(Kind::Trivia, " \t"));
+        match tree[1] {
+            Element::Node(ref node) => {
+                assert_eq!(node.kind, Kind::Call);
+                assert_eq!(node.children.len(), 13);
+                assert_eq!(node.children[12], leaf_element(Kind::Trivia, "  "));
+                assert_eq!(node.text_len(), input.len() - 2);
+            }
+            _ => panic!("Expected a node")
+        }
+        let text: String = tree.iter().map(|element| element.to_string()).collect();
+        assert_eq!(text, input);
+        assert_eq!(rest.offset(), input.len());
+    }
+
+    #[test]
+    fn nested_leaves_are_replaced() {
+        let inner = leaf(Kind::Ident, letter());
+        let mut parser = leaf(Kind::Call, (inner, node(Kind::Call, digit())));
+        let (_, rest) = parser.parse(CstStream::new("a1b", Kind::Trivia)).unwrap();
+        assert_eq!(rest.tree().unwrap(), vec![leaf_element(Kind::Call, "a1")]);
+        assert_eq!(rest.into_inner(), "b");
+    }
+}
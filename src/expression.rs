@@ -0,0 +1,392 @@
+use std::marker::PhantomData;
+use primitives::{Consumed, Error, Info, Parser, ParseError, ParseResult, State, Stream};
+
+///Enum specifying the associativity of an infix operator
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Assoc {
+    ///`a + b + c` is parsed as `(a + b) + c`
+    Left,
+    ///`a ^ b ^ c` is parsed as `a ^ (b ^ c)`
+    Right,
+    ///`a == b == c` is a parse error
+    None
+}
+
+///Enum specifying where an operator is placed in relation to its operand(s)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fixity {
+    ///The operator is placed before its operand, `-a`
+    Prefix,
+    ///The operator is placed between its operands, `a + b`
+    Infix(Assoc),
+    ///The operator is placed after its operand, `a!`
+    Postfix
+}
+
+///An entry in the operator table passed to `expression_parser`.
+///Operators with a higher `precedence` binds tighter than operators with a lower `precedence`.
+#[derive(Clone)]
+pub struct Operator<P> {
+    ///The parser which parses the operator itself
+    pub parser: P,
+    pub fixity: Fixity,
+    pub precedence: u32
+}
+
+impl <P: Parser> Operator<P> {
+    ///Creates a prefix operator
+    pub fn prefix(precedence: u32, parser: P) -> Operator<P> {
+        Operator { parser: parser, fixity: Fixity::Prefix, precedence: precedence }
+    }
+
+    ///Creates an infix operator with the associativity `assoc`
+    pub fn infix(precedence: u32, assoc: Assoc, parser: P) -> Operator<P> {
+        Operator { parser: parser, fixity: Fixity::Infix(assoc), precedence: precedence }
+    }
+
+    ///Creates a postfix operator
+    pub fn postfix(precedence: u32, parser: P) -> Operator<P> {
+        Operator { parser: parser, fixity: Fixity::Postfix, precedence: precedence }
+    }
+}
+
+///Enum which is passed to the function given to `expression_parser` when an operator has been
+///parsed together with its operand(s).
+///`E` is the type of the expressions and `T` is the type output by the operator parsers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation<E, T> {
+    Prefix(T, E),
+    Infix(E, T, E),
+    Postfix(E, T)
+}
+
+pub struct Expression<T, S, P, F>
+    where P: Parser {
+    term: T,
+    table: S,
+    f: F,
+    //The merged errors of the operators which failed after the last operand
+    trailing: Option<ParseError<P::Input>>,
+    _marker: PhantomData<fn (P) -> P>
+}
+
+impl <T, S, P, F> Clone for Expression<T, S, P, F>
+    where T: Clone
+        , S: Clone
+        , P: Parser
+        , F: Clone {
+    fn clone(&self) -> Expression<T, S, P, F> {
+        Expression {
+            term: self.term.clone(),
+            table: self.table.clone(),
+            f: self.f.clone(),
+            trailing: None,
+            _marker: PhantomData
+        }
+    }
+}
+
+impl <I, E, T, S, P, F> Expression<T, S, P, F>
+    where I: Stream
+        , T: Parser<Input=I, Output=E>
+        , S: AsMut<[Operator<P>]>
+        , P: Parser<Input=I>
+        , F: FnMut(Operation<E, P::Output>) -> E {
+
+    ///Parses either a term or a prefix operator followed by its operand
+    fn parse_operand(&mut self, input: State<I>) -> ParseResult<E, I> {
+        let mut empty_err = None;
+        for i in 0..self.table.as_mut().len() {
+            let (op, rest) = {
+                let operator = &mut self.table.as_mut()[i];
+                if operator.fixity != Fixity::Prefix {
+                    continue
+                }
+                match operator.parser.parse_lazy(input.clone()) {
+                    Ok((op, rest)) => (op, rest),
+                    Err(Consumed::Empty(err)) => {
+                        empty_err = match empty_err {
+                            None => Some(err),
+                            Some(prev_err) => Some(prev_err.merge(err))
+                        };
+                        continue
+                    }
+                    Err(err) => return Err(err)
+                }
+            };
+            let precedence = self.table.as_mut()[i].precedence;
+            return rest.combine(|rest| {
+                let (operand, rest) = try!(self.parse_state_expr(rest, precedence as u64));
+                Ok(((self.f)(Operation::Prefix(op, operand)), rest))
+            })
+        }
+        match self.term.parse_lazy(input) {
+            Err(Consumed::Empty(err)) => Err(Consumed::Empty(match empty_err {
+                None => err,
+                Some(prev_err) => prev_err.merge(err)
+            })),
+            result => result
+        }
+    }
+
+    ///Parses an expression which only contains operators with a precedence of at least
+    ///`min_precedence`
+    fn parse_expr(&mut self, input: State<I>, min_precedence: u64) -> ParseResult<E, I> {
+        let (mut lhs, mut input) = try!(self.parse_operand(input));
+        let mut non_assoc_precedence = None;
+        loop {
+            let was_empty = input.is_empty();
+            let rest = input.clone().into_inner();
+            let mut found = None;
+            let mut empty_err = None;
+            for (i, operator) in self.table.as_mut().iter_mut().enumerate() {
+                if operator.fixity == Fixity::Prefix || (operator.precedence as u64) < min_precedence {
+                    continue
+                }
+                match operator.parser.parse_lazy(rest.clone()) {
+                    Ok((op, rest)) => {
+                        found = Some((i, op, rest));
+                        break
+                    }
+                    Err(Consumed::Empty(mut err)) => {
+                        operator.parser.add_error(&mut err);
+                        empty_err = match empty_err {
+                            None => Some(err),
+                            Some(prev_err) => Some(prev_err.merge(err))
+                        };
+                    }
+                    Err(err) => return Err(err)
+                }
+            }
+            let (i, op, op_rest) = match found {
+                Some(x) => x,
+                None => {
+                    self.trailing = empty_err;
+                    break
+                }
+            };
+            let (fixity, precedence) = {
+                let operator = &self.table.as_mut()[i];
+                (operator.fixity, operator.precedence)
+            };
+            if non_assoc_precedence == Some(precedence) {
+                let error = Error::Message("Non-associative operators cannot be chained".into());
+                return Err(Consumed::Consumed(ParseError::new(rest.position, error)))
+            }
+            let (expr, op_rest) = match fixity {
+                Fixity::Postfix => ((self.f)(Operation::Postfix(lhs, op)), op_rest),
+                Fixity::Infix(assoc) => {
+                    let next_precedence = match assoc {
+                        Assoc::Right => precedence as u64,
+                        Assoc::Left | Assoc::None => precedence as u64 + 1
+                    };
+                    if assoc == Assoc::None {
+                        non_assoc_precedence = Some(precedence);
+                    }
+                    let (rhs, op_rest) = try!(op_rest.combine(|op_rest| {
+                        self.parse_state_expr(op_rest, next_precedence)
+                    }));
+                    ((self.f)(Operation::Infix(lhs, op, rhs)), op_rest)
+                }
+                Fixity::Prefix => unreachable!()
+            };
+            lhs = expr;
+            input = if was_empty { op_rest } else { op_rest.as_consumed() };
+        }
+        Ok((lhs, input))
+    }
+
+    ///Same as `parse_expr` but adds the expected errors if no input were consumed
+    fn parse_state_expr(&mut self, input: State<I>, min_precedence: u64) -> ParseResult<E, I> {
+        let mut result = self.parse_expr(input.clone(), min_precedence);
+        if let Err(Consumed::Empty(ref mut error)) = result {
+            if let Ok((t, _)) = input.input.uncons() {
+                error.add_error(Error::Unexpected(Info::Token(t)));
+            }
+            self.add_operand_error(error);
+        }
+        result
+    }
+
+    fn add_operand_error(&mut self, error: &mut ParseError<I>) {
+        for operator in self.table.as_mut() {
+            if operator.fixity == Fixity::Prefix {
+                operator.parser.add_error(error);
+            }
+        }
+        self.term.add_error(error);
+    }
+}
+
+impl <I, E, T, S, P, F> Parser for Expression<T, S, P, F>
+    where I: Stream
+        , T: Parser<Input=I, Output=E>
+        , S: AsMut<[Operator<P>]>
+        , P: Parser<Input=I>
+        , F: FnMut(Operation<E, P::Output>) -> E {
+
+    type Input = I;
+    type Output = E;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<E, I> {
+        self.parse_expr(input, 0)
+    }
+    fn add_error(&mut self, error: &mut ParseError<Self::Input>) {
+        self.add_operand_error(error)
+    }
+    fn add_trailing_error(&mut self, error: &mut ParseError<Self::Input>) {
+        if let Some(trailing) = self.trailing.take() {
+            for err in trailing.errors {
+                error.add_error(err);
+            }
+        }
+    }
+}
+
+///Constructs an expression parser out of a `term` parser and a `table` of operators.
+///Each operator has a fixity and a precedence, operators with a higher precedence bind tighter
+///than operators with a lower precedence. `f` is called with the parsed operator and its operands
+///to build the resulting expression.
+///
+///If more than one operator in the table can parse the same input, the one appearing first in
+///the table is used.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::expression::{expression_parser, Assoc, Operation, Operator};
+/// # fn main() {
+/// let number = many1(digit()).map(|s: String| s.parse::<i64>().unwrap());
+/// let mut table = [
+///     Operator::infix(6, Assoc::Left, char('+')),
+///     Operator::infix(6, Assoc::Left, char('-')),
+///     Operator::infix(7, Assoc::Left, char('*')),
+///     Operator::infix(8, Assoc::Right, char('^')),
+///     Operator::prefix(9, char('-')),
+///     Operator::postfix(10, char('!')),
+/// ];
+/// let mut expr = expression_parser(number, &mut table[..], |operation| {
+///     match operation {
+///         Operation::Prefix(_, x) => -x,
+///         Operation::Infix(l, '+', r) => l + r,
+///         Operation::Infix(l, '-', r) => l - r,
+///         Operation::Infix(l, '*', r) => l * r,
+///         Operation::Infix(l, _, r) => l.pow(r as u32),
+///         Operation::Postfix(x, _) => (1..x + 1).product(),
+///     }
+/// });
+/// assert_eq!(expr.parse("1+2*3!-4"), Ok((9, "")));
+/// assert_eq!(expr.parse("2^3^2"), Ok((512, "")));
+/// assert_eq!(expr.parse("-2*3"), Ok((-6, "")));
+/// # }
+/// ```
+pub fn expression_parser<I, E, T, S, P, F>(term: T, table: S, f: F) -> Expression<T, S, P, F>
+    where I: Stream
+        , T: Parser<Input=I, Output=E>
+        , S: AsMut<[Operator<P>]>
+        , P: Parser<Input=I>
+        , F: FnMut(Operation<E, P::Output>) -> E {
+    Expression { term: term, table: table, f: f, trailing: None, _marker: PhantomData }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::{Error, Parser, SourcePosition};
+    use combinator::{many1, ParserExt};
+    use char::{char, digit};
+
+    fn show(operation: Operation<String, char>) -> String {
+        match operation {
+            Operation::Prefix(op, x) => format!("({}{})", op, x),
+            Operation::Infix(l, op, r) => format!("({}{}{})", l, op, r),
+            Operation::Postfix(x, op) => format!("({}{})", x, op),
+        }
+    }
+
+    fn table() -> Vec<Operator<::combinator::Token<&'static str>>> {
+        vec![
+            Operator::infix(4, Assoc::None, char('=')),
+            Operator::infix(6, Assoc::Left, char('+')),
+            Operator::infix(6, Assoc::Left, char('-')),
+            Operator::infix(7, Assoc::Left, char('*')),
+            Operator::infix(8, Assoc::Right, char('^')),
+            Operator::prefix(5, char('!')),
+            Operator::prefix(9, char('-')),
+            Operator::postfix(10, char('?')),
+        ]
+    }
+
+    fn parse(input: &'static str) -> Result<(String, &'static str), ::primitives::ParseError<&'static str>> {
+        let mut table = table();
+        expression_parser(many1(digit()), &mut table[..], show)
+            .parse(input)
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(parse("1+2*3-4"), Ok(("((1+(2*3))-4)".to_string(), "")));
+        assert_eq!(parse("1^2^3*4"), Ok(("((1^(2^3))*4)".to_string(), "")));
+        assert_eq!(parse("1=2+3"), Ok(("(1=(2+3))".to_string(), "")));
+    }
+
+    #[test]
+    fn prefix_and_postfix() {
+        assert_eq!(parse("-1*2"), Ok(("((-1)*2)".to_string(), "")));
+        assert_eq!(parse("!1+2"), Ok(("(!(1+2))".to_string(), "")));
+        assert_eq!(parse("1+2?"), Ok(("(1+(2?))".to_string(), "")));
+        assert_eq!(parse("--1??"), Ok(("(-(-((1?)?)))".to_string(), "")));
+    }
+
+    #[test]
+    fn non_assoc_error() {
+        assert!(parse("1=2=3").is_err());
+        assert_eq!(parse("1=2+3=4").map_err(|err| err.position),
+                   Err(SourcePosition { line: 1, column: 6, offset: 5, utf16_column: 6 }));
+    }
+
+    #[test]
+    fn missing_operand_error() {
+        let err = parse("1+*").unwrap_err();
+        assert_eq!(err.position, SourcePosition { line: 1, column: 3, offset: 2, utf16_column: 3 });
+        assert_eq!(err.errors, vec![
+            Error::Unexpected('*'.into()),
+            Error::Expected('!'.into()),
+            Error::Expected('-'.into()),
+            Error::Expected("digit".into()),
+        ]);
+    }
+
+    #[test]
+    fn trailing_input() {
+        assert_eq!(parse("1+2)"), Ok(("(1+2)".to_string(), ")")));
+    }
+
+    #[test]
+    fn expected_operator_error() {
+        let mut table = table();
+        let err = expression_parser(many1(digit()), &mut table[..], show)
+            .skip(char(';'))
+            .parse("1 2")
+            .unwrap_err();
+        assert_eq!(err.position, SourcePosition { line: 1, column: 2, offset: 1, utf16_column: 2 });
+        assert_eq!(err.errors, vec![
+            Error::Unexpected(' '.into()),
+            Error::Expected(';'.into()),
+            Error::Expected('='.into()),
+            Error::Expected('+'.into()),
+            Error::Expected('-'.into()),
+            Error::Expected('*'.into()),
+            Error::Expected('^'.into()),
+            Error::Expected('?'.into()),
+        ]);
+    }
+
+    #[test]
+    fn max_precedence() {
+        let mut table = [
+            Operator::infix(u32::max_value(), Assoc::Left, char('+')),
+        ];
+        let result = expression_parser(many1(digit()), &mut table[..], show).parse("1+2+3");
+        assert_eq!(result, Ok(("((1+2)+3)".to_string(), "")));
+    }
+}
@@ -0,0 +1,295 @@
+use std::marker::PhantomData;
+use primitives::{Consumed, Error, Info, Parser, ParseError, ParseResult, RangeStream, SourcePosition,
+                 State, Stream};
+use combinator::{many1, Many1, ParserExt, With};
+use memo::MemoStream;
+
+///Wrapper around a character stream which stores a reference position that the parsers in this
+///module compare the current position against.
+///The reference position starts out at the start of the input and is changed by `with_pos`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndentStream<I> {
+    input: I,
+    reference: SourcePosition
+}
+
+impl <I> IndentStream<I>
+    where I: Stream<Item=char> {
+    ///Wraps `input` to allow it to be used with the indentation sensitive parsers in this module
+    pub fn new(input: I) -> IndentStream<I> {
+        IndentStream { input: input, reference: <char as ::primitives::Positioner>::start() }
+    }
+
+    ///Returns the current reference position
+    pub fn reference(&self) -> SourcePosition {
+        self.reference
+    }
+
+    ///Returns the wrapped stream
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl <I> Stream for IndentStream<I>
+    where I: Stream<Item=char> {
+    type Item = char;
+    type Range = I::Range;
+    fn uncons(self) -> Result<(char, IndentStream<I>), Error<char, I::Range>> {
+        let (c, input) = try!(self.input.uncons());
+        Ok((c, IndentStream { input: input, reference: self.reference }))
+    }
+    fn update_position(&self, item: &char, position: &mut SourcePosition) {
+        self.input.update_position(item, position)
+    }
+    fn update_range_position(&self, range: &I::Range, position: &mut SourcePosition) {
+        self.input.update_range_position(range, position)
+    }
+    fn is_partial(&self) -> bool {
+        self.input.is_partial()
+    }
+    fn start_position(&self) -> SourcePosition {
+        self.input.start_position()
+    }
+}
+
+impl <I> MemoStream for IndentStream<I>
+    where I: MemoStream<Item=char> {
+    type Key = (I::Key, SourcePosition);
+    fn memo_key(&self) -> (I::Key, SourcePosition) {
+        (self.input.memo_key(), self.reference)
+    }
+}
+
+impl <I> RangeStream for IndentStream<I>
+    where I: RangeStream<Item=char> {
+    fn uncons_range(self, size: usize) -> Result<(I::Range, IndentStream<I>), Error<char, I::Range>> {
+        let (range, input) = try!(self.input.uncons_range(size));
+        Ok((range, IndentStream { input: input, reference: self.reference }))
+    }
+    fn uncons_while<F>(self, predicate: F) -> Result<(I::Range, IndentStream<I>), Error<char, I::Range>>
+        where F: FnMut(char) -> bool {
+        let (range, input) = try!(self.input.uncons_while(predicate));
+        Ok((range, IndentStream { input: input, reference: self.reference }))
+    }
+}
+
+#[derive(Clone)]
+pub struct WithPos<P>(P);
+impl <I, P> Parser for WithPos<P>
+    where I: Stream<Item=char>
+        , P: Parser<Input=IndentStream<I>> {
+    type Input = IndentStream<I>;
+    type Output = P::Output;
+    fn parse_lazy(&mut self, mut input: State<IndentStream<I>>) -> ParseResult<P::Output, IndentStream<I>> {
+        let previous = input.input.reference;
+        input.input.reference = input.position;
+        let (value, rest) = try!(self.0.parse_lazy(input));
+        Ok((value, rest.map(|mut rest| {
+            rest.input.reference = previous;
+            rest
+        })))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.0.add_error(errors)
+    }
+}
+
+///Parses with `p` using the current position as the reference position.
+///The previous reference position is restored once `p` has finished.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::indent::{indented, with_pos, IndentStream};
+/// # fn main() {
+/// //A key followed by a value which must be indented further than the key
+/// let mut parser = spaces().with(with_pos((many1::<String, _>(letter()).skip(char(':')).skip(spaces()),
+///                                          indented(many1::<String, _>(digit())))));
+/// let result = parser.parse(IndentStream::new("  key:\n    123"))
+///     .map(|x| x.0);
+/// assert_eq!(result, Ok(("key".to_string(), "123".to_string())));
+/// let result = parser.parse(IndentStream::new("  key:\n  123"));
+/// assert!(result.is_err());
+/// # }
+/// ```
+pub fn with_pos<P>(p: P) -> WithPos<P>
+    where P: Parser {
+    WithPos(p)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Check {
+    Indented,
+    SameLine,
+    Aligned
+}
+
+#[derive(Clone)]
+pub struct CheckPosition<I>(Check, PhantomData<fn (I) -> I>);
+impl <I> Parser for CheckPosition<I>
+    where I: Stream<Item=char> {
+    type Input = IndentStream<I>;
+    type Output = ();
+    fn parse_lazy(&mut self, input: State<IndentStream<I>>) -> ParseResult<(), IndentStream<I>> {
+        let position = input.position;
+        let reference = input.input.reference;
+        let expected = match self.0 {
+            Check::Indented if position.column <= reference.column => {
+                format!("indentation greater than {}", reference.column - 1)
+            }
+            Check::SameLine if position.line != reference.line => {
+                format!("token on line {}", reference.line)
+            }
+            Check::Aligned if position.column != reference.column => {
+                format!("indentation of {}", reference.column - 1)
+            }
+            _ => return Ok(((), Consumed::Empty(input)))
+        };
+        //The expected message depends on the reference position so it can't be added through
+        //`add_error`, add the unexpected token here instead to keep the usual order of errors
+        let mut error = ParseError::empty(position);
+        if let Ok((c, _)) = input.input.uncons() {
+            error.add_error(Error::Unexpected(Info::Token(c)));
+        }
+        error.add_error(Error::Expected(Info::Owned(expected)));
+        Err(Consumed::Empty(error))
+    }
+}
+
+///Succeeds without consuming input if the current column is equal to the column of the reference
+///position.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::indent::{check_indent, IndentStream};
+/// # fn main() {
+/// let result = spaces().with(check_indent())
+///     .parse(IndentStream::new("  a"));
+/// assert!(result.is_err());
+/// # }
+/// ```
+pub fn check_indent<I>() -> CheckPosition<I>
+    where I: Stream<Item=char> {
+    CheckPosition(Check::Aligned, PhantomData)
+}
+
+///Parses with `p` if the current column is greater than the column of the reference position.
+///Fails with an error such as "expected indentation greater than 4" otherwise.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::indent::{indented, IndentStream};
+/// # fn main() {
+/// let mut parser = spaces().with(indented(letter()));
+/// assert_eq!(parser.parse(IndentStream::new(" a")).map(|x| x.0), Ok('a'));
+/// assert!(parser.parse(IndentStream::new("a")).is_err());
+/// # }
+/// ```
+pub fn indented<I, P>(p: P) -> With<CheckPosition<I>, P>
+    where I: Stream<Item=char>
+        , P: Parser<Input=IndentStream<I>> {
+    CheckPosition(Check::Indented, PhantomData).with(p)
+}
+
+///Parses with `p` if the current line is the same as the line of the reference position.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::indent::{same_line, with_pos, IndentStream};
+/// # fn main() {
+/// let mut parser = with_pos((letter(), spaces().with(same_line(letter()))));
+/// assert_eq!(parser.parse(IndentStream::new("a b")).map(|x| x.0), Ok(('a', 'b')));
+/// assert!(parser.parse(IndentStream::new("a\nb")).is_err());
+/// # }
+/// ```
+pub fn same_line<I, P>(p: P) -> With<CheckPosition<I>, P>
+    where I: Stream<Item=char>
+        , P: Parser<Input=IndentStream<I>> {
+    CheckPosition(Check::SameLine, PhantomData).with(p)
+}
+
+///Parses one or more `p` which must all start at the same column as the first `p`.
+///`p` is expected to skip any trailing whitespace itself so that the next element of the block
+///starts directly after it.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::indent::{block, IndentStream};
+/// # fn main() {
+/// let item = many1::<String, _>(letter()).skip(spaces());
+/// let mut parser = spaces().with(block(item));
+/// let result = parser.parse(IndentStream::new("  a\n  b\n c"))
+///     .map(|(items, rest)| (items, rest.into_inner()));
+/// assert_eq!(result, Ok((vec!["a".to_string(), "b".to_string()], "c")));
+/// # }
+/// ```
+pub fn block<I, P>(p: P) -> WithPos<Many1<Vec<P::Output>, With<CheckPosition<I>, P>>>
+    where I: Stream<Item=char>
+        , P: Parser<Input=IndentStream<I>> {
+    with_pos(many1(check_indent().with(p)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::{Error, Parser, SourcePosition};
+    use combinator::{many1, ParserExt};
+    use char::{char, digit, letter, spaces};
+
+    #[derive(Debug, PartialEq)]
+    enum Value {
+        Number(String),
+        Object(Vec<(String, Value)>)
+    }
+
+    fn value(input: State<IndentStream<&'static str>>) -> ParseResult<Value, IndentStream<&'static str>> {
+        let number = many1(digit()).skip(spaces()).map(Value::Number);
+        let object = block(::combinator::parser(entry)).map(Value::Object);
+        number.or(object).parse_state(input)
+    }
+
+    fn entry(input: State<IndentStream<&'static str>>) -> ParseResult<(String, Value), IndentStream<&'static str>> {
+        with_pos((many1(letter()).skip(char(':')).skip(spaces()), indented(::combinator::parser(value))))
+            .parse_state(input)
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let input = "a:\n  b: 1\n  c:\n    d: 2\ne: 3\n";
+        let result = ::combinator::parser(value).parse(IndentStream::new(input))
+            .map(|(v, rest)| (v, rest.into_inner()));
+        assert_eq!(result, Ok((Value::Object(vec![
+            ("a".to_string(), Value::Object(vec![
+                ("b".to_string(), Value::Number("1".to_string())),
+                ("c".to_string(), Value::Object(vec![
+                    ("d".to_string(), Value::Number("2".to_string()))
+                ]))
+            ])),
+            ("e".to_string(), Value::Number("3".to_string()))
+        ]), "")));
+    }
+
+    #[test]
+    fn indented_error() {
+        let mut parser = spaces().with(with_pos((letter(), spaces().with(indented(digit())))));
+        let result = parser.parse(IndentStream::new("    a\n    1"));
+        assert_eq!(result.map_err(|err| (err.position, err.errors)), Err((
+            SourcePosition { line: 2, column: 5, offset: 10, utf16_column: 5 },
+            vec![Error::Unexpected('1'.into()),
+                 Error::Expected("indentation greater than 4".to_string().into())]
+        )));
+    }
+
+    #[test]
+    fn reference_is_restored() {
+        let mut parser = (with_pos(letter().skip(spaces())), ::combinator::position(), spaces());
+        let result = parser.parse(IndentStream::new("a\n b"));
+        let (_, rest) = result.unwrap();
+        assert_eq!(rest.reference(), <char as ::primitives::Positioner>::start());
+    }
+}
@@ -0,0 +1,563 @@
+use std::cell::RefCell;
+use std::error::Error as StdError;
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use primitives::{Consumed, Error, IndexPosition, Parser, ParseError, Positioner, Span, State, Stream};
+
+///A token produced by a lexer together with the span of the source it was lexed from.
+///`P` is the type of the positions in the source, for instance `SourcePosition`.
+///
+///`Lexeme` is the item type of both `TokenStream` and `LocatedSlice`. When a stream of lexemes does
+///not contain any lexeme its position is `P::default()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lexeme<T, P> {
+    ///The token
+    pub token: T,
+    ///The span of the source which the token was lexed from
+    pub span: Span<P>
+}
+
+impl <T, P> Positioner for Lexeme<T, P>
+    where T: PartialEq
+        , P: Clone + Ord + Default {
+    type Position = P;
+    fn start() -> P {
+        P::default()
+    }
+    fn update(&self, position: &mut P) {
+        *position = self.span.end.clone();
+    }
+}
+
+impl <T, P> fmt::Display for Lexeme<T, P>
+    where T: fmt::Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.token.fmt(f)
+    }
+}
+
+///Error returned when parsing a `TokenStream` reaches the point where the lexer failed.
+///It holds the messages of the `ParseError` returned by the lexer and is reported as an
+///`Error::Other` at the position in the source where the lexer failed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LexError {
+    messages: Vec<String>
+}
+
+impl LexError {
+    fn new<I>(error: &ParseError<I>) -> LexError
+        where I: Stream
+            , I::Item: fmt::Display
+            , I::Range: fmt::Display {
+        LexError { messages: error.errors.iter().map(|error| error.to_string()).collect() }
+    }
+
+    fn message(message: &str) -> LexError {
+        LexError { messages: vec![message.to_string()] }
+    }
+
+    ///Returns the messages of the errors returned by the lexer
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, message) in self.messages.iter().enumerate() {
+            if i != 0 {
+                try!(writeln!(f, ""));
+            }
+            try!(write!(f, "{}", message));
+        }
+        Ok(())
+    }
+}
+
+impl StdError for LexError {
+    fn description(&self) -> &str { "lexer error" }
+}
+
+//The position and error of a lexer which stopped because of an error
+type Failure<P> = Rc<RefCell<Option<(P, LexError)>>>;
+
+struct SharedTokens<I>
+    where I: Iterator
+        , I::Item: Positioner {
+    iter: I,
+    tokens: Vec<I::Item>
+}
+
+impl <I> SharedTokens<I>
+    where I: Iterator
+        , I::Item: Positioner + Clone {
+    fn get(&mut self, index: usize) -> Option<I::Item> {
+        while self.tokens.len() <= index {
+            match self.iter.next() {
+                Some(token) => self.tokens.push(token),
+                None => return None
+            }
+        }
+        Some(self.tokens[index].clone())
+    }
+}
+
+///Stream over the lexemes produced by an iterator, usually a lexer created by `lex`.
+///Lexemes are only pulled from the iterator when they are first needed and are then kept so that
+///backtracking does not need to lex the input again.
+///
+///Positions reported while parsing a `TokenStream` are positions in the original source, after a
+///lexeme has been consumed the position is the start of the next lexeme or, if there are no more
+///lexemes, the end of the last lexeme.
+pub struct TokenStream<I>
+    where I: Iterator
+        , I::Item: Positioner {
+    shared: Rc<RefCell<SharedTokens<I>>>,
+    failure: Failure<<I::Item as Positioner>::Position>,
+    index: usize
+}
+
+impl <I> Clone for TokenStream<I>
+    where I: Iterator
+        , I::Item: Positioner {
+    fn clone(&self) -> TokenStream<I> {
+        TokenStream { shared: self.shared.clone(), failure: self.failure.clone(), index: self.index }
+    }
+}
+
+impl <I> fmt::Debug for TokenStream<I>
+    where I: Iterator
+        , I::Item: Positioner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TokenStream {{ index: {} }}", self.index)
+    }
+}
+
+impl <I> TokenStream<I>
+    where I: Iterator
+        , I::Item: Positioner {
+    ///Creates a stream over the lexemes produced by `iter`
+    pub fn new(iter: I) -> TokenStream<I> {
+        TokenStream::with_failure(iter, Rc::new(RefCell::new(None)))
+    }
+
+    fn with_failure(iter: I, failure: Failure<<I::Item as Positioner>::Position>) -> TokenStream<I> {
+        TokenStream {
+            shared: Rc::new(RefCell::new(SharedTokens { iter: iter, tokens: Vec::new() })),
+            failure: failure,
+            index: 0
+        }
+    }
+
+    ///Returns the number of lexemes which have been taken from the stream
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    ///Returns the underlying iterator if this is the only remaining handle to it
+    pub fn into_iter(self) -> Option<I> {
+        Rc::try_unwrap(self.shared)
+            .ok()
+            .map(|shared| shared.into_inner().iter)
+    }
+}
+
+impl <I, T, P> Stream for TokenStream<I>
+    where I: Iterator<Item=Lexeme<T, P>>
+        , T: Clone + PartialEq
+        , P: Clone + Ord + Default {
+    type Item = Lexeme<T, P>;
+    type Range = Lexeme<T, P>;
+    fn uncons(self) -> Result<(Lexeme<T, P>, TokenStream<I>), Error<Lexeme<T, P>, Lexeme<T, P>>> {
+        let token = self.shared.borrow_mut().get(self.index);
+        match token {
+            Some(token) => {
+                let index = self.index + 1;
+                Ok((token, TokenStream { index: index, .. self }))
+            }
+            None => {
+                match *self.failure.borrow() {
+                    Some((_, ref error)) => Err(Error::Other(Box::new(error.clone()))),
+                    None => Err(Error::end_of_input())
+                }
+            }
+        }
+    }
+    fn update_position(&self, item: &Lexeme<T, P>, position: &mut P) {
+        //Point at the start of the next lexeme so that errors are reported where the unexpected
+        //lexeme begins instead of at the end of the previous one
+        *position = self.next_position().unwrap_or_else(|| item.span.end.clone());
+    }
+    fn start_position(&self) -> P {
+        self.next_position()
+            .or_else(|| {
+                if self.index == 0 {
+                    return None
+                }
+                self.shared.borrow_mut().get(self.index - 1).map(|previous| previous.span.end)
+            })
+            .unwrap_or_else(P::default)
+    }
+}
+
+impl <I, T, P> TokenStream<I>
+    where I: Iterator<Item=Lexeme<T, P>>
+        , T: Clone + PartialEq
+        , P: Clone + Ord + Default {
+    //Returns the start of the next lexeme or, if the lexer failed before producing it, the
+    //position where the lexer failed
+    fn next_position(&self) -> Option<P> {
+        let next = self.shared.borrow_mut().get(self.index);
+        match next {
+            Some(next) => Some(next.span.start),
+            None => self.failure.borrow().as_ref().map(|failure| failure.0.clone())
+        }
+    }
+}
+
+///Iterator which runs a lexer repeatedly, returned inside the `TokenStream` created by `lex`.
+///The iterator stops at the end of the input or at the first error returned by the lexer, in
+///which case parsing the `TokenStream` fails with a `LexError` at that point. It also stops with
+///a `LexError` if the lexer succeeds without consuming any input.
+pub struct Tokenizer<P>
+    where P: Parser {
+    parser: P,
+    input: Option<State<P::Input>>,
+    failure: Failure<<<P::Input as Stream>::Item as Positioner>::Position>
+}
+
+impl <I, P, T> Iterator for Tokenizer<P>
+    where I: Stream
+        , I::Item: fmt::Display
+        , I::Range: fmt::Display
+        , P: Parser<Input=I, Output=(T, Span<<I::Item as Positioner>::Position>)> {
+    type Item = Lexeme<T, <I::Item as Positioner>::Position>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = match self.input.take() {
+            Some(input) => input,
+            None => return None
+        };
+        match self.parser.parse_state(input.clone()) {
+            //A lexer which does not consume any input would return the same lexeme forever so
+            //lexing stops, which is an error unless the end of the input was reached
+            Ok((_, Consumed::Empty(_))) => {
+                if input.input.uncons().is_ok() {
+                    let error = LexError::message("lexer did not consume any input");
+                    *self.failure.borrow_mut() = Some((input.position, error));
+                }
+                None
+            }
+            Ok(((token, span), rest)) => {
+                self.input = Some(rest.into_inner());
+                Some(Lexeme { token: token, span: span })
+            }
+            Err(error) => {
+                //Stopping at the end of the input is expected but anywhere else the lexer failed
+                if input.input.uncons().is_ok() {
+                    let error = error.into_inner();
+                    *self.failure.borrow_mut() = Some((error.position.clone(), LexError::new(&error)));
+                }
+                None
+            }
+        }
+    }
+}
+
+///The type of the stream returned by `lex`
+pub type Lexer<P> = TokenStream<Tokenizer<P>>;
+
+///Runs `lexer` repeatedly on `input`, turning the tokens it returns into a `TokenStream` which can
+///then be parsed by a second, token level, parser.
+///`lexer` should return each token together with its span, as returned by `ParserExt::with_span`,
+///and skip any whitespace after the token. If `lexer` fails before the end of the input, parsing
+///the `TokenStream` fails with a `LexError` when it reaches that point.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::lexer::{lex, Lexeme};
+/// # use pc::primitives::SourcePosition;
+/// # fn main() {
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Token {
+///     Number(i32),
+///     Plus
+/// }
+/// let tokens = |input| {
+///     let number = many1(digit()).map(|s: String| Token::Number(s.parse().unwrap()));
+///     let plus = char('+').map(|_| Token::Plus);
+///     lex(number.or(plus).with_span().skip(spaces()), input)
+/// };
+///
+/// let number = satisfy(|l: Lexeme<Token, SourcePosition>| l.token != Token::Plus)
+///     .map(|l: Lexeme<Token, SourcePosition>| match l.token { Token::Number(n) => n, _ => unreachable!() });
+/// let plus = satisfy(|l: Lexeme<Token, SourcePosition>| l.token == Token::Plus);
+/// let mut sum = sep_by(number, plus).map(|ns: Vec<i32>| ns.iter().fold(0, |x, y| x + y));
+/// assert_eq!(sum.parse(tokens("1 + 23 + 4")).map(|x| x.0), Ok(28));
+///
+/// //Errors refer to positions in the source
+/// let error = sum.parse(tokens("1 +\n + 2")).map(|x| x.0).unwrap_err();
+/// assert_eq!((error.position.line, error.position.column), (2, 2));
+///
+/// //As are errors from the lexer
+/// let error = sum.parse(tokens("1 + #")).map(|x| x.0).unwrap_err();
+/// assert_eq!((error.position.line, error.position.column), (1, 5));
+/// # }
+/// ```
+pub fn lex<I, P, T>(lexer: P, input: I) -> Lexer<P>
+    where I: Stream
+        , T: PartialEq
+        , I::Item: fmt::Display
+        , <I::Item as Positioner>::Position: Default
+        , I::Range: fmt::Display
+        , P: Parser<Input=I, Output=(T, Span<<I::Item as Positioner>::Position>)> {
+    let failure = Rc::new(RefCell::new(None));
+    let tokenizer = Tokenizer { parser: lexer, input: Some(State::new(input)), failure: failure.clone() };
+    TokenStream::with_failure(tokenizer, failure)
+}
+
+///Stream over a slice of tokens which do not implement `Positioner`, such as the tokens produced
+///by a hand-written lexer. The span of each token is computed by a function which is given the
+///index of the token and the token itself, and the tokens are returned as lexemes with that span.
+///
+///After a token has been taken the position is the start of the next token, or the end of the
+///last token if the end of the slice has been reached.
+pub struct LocatedSlice<'a, T: 'a, P, F> {
+    slice: &'a [T],
+    index: usize,
+    locate: F,
+    _marker: PhantomData<fn (P) -> P>
+}
+
+impl <'a, T, P, F> Clone for LocatedSlice<'a, T, P, F>
+    where F: Clone {
+    fn clone(&self) -> LocatedSlice<'a, T, P, F> {
+        LocatedSlice { slice: self.slice, index: self.index, locate: self.locate.clone(), _marker: PhantomData }
+    }
+}
+
+impl <'a, T, P, F> fmt::Debug for LocatedSlice<'a, T, P, F>
+    where T: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LocatedSlice {{ slice: {:?} }}", &self.slice[self.index..])
+    }
+}
+
+impl <'a, T, P, F> LocatedSlice<'a, T, P, F>
+    where P: Clone + Ord + Default
+        , F: Fn(usize, &T) -> Span<P> + Clone {
+    ///Creates a stream over `slice` where the span of each token is returned by `locate`
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::lexer::{Lexeme, LocatedSlice};
+    /// # use pc::primitives::{SourcePosition, Span};
+    /// # fn main() {
+    /// #[derive(Debug, PartialEq)]
+    /// struct Token {
+    ///     text: &'static str,
+    ///     position: SourcePosition
+    /// }
+    /// fn at(line: i32, column: i32) -> SourcePosition {
+    ///     SourcePosition::new(line, column)
+    /// }
+    /// let tokens = [
+    ///     Token { text: "let", position: at(1, 1) },
+    ///     Token { text: "x", position: at(1, 5) },
+    ///     Token { text: "let", position: at(2, 1) }
+    /// ];
+    /// let input = LocatedSlice::new(&tokens[..], |_, token: &Token| {
+    ///     let end = at(token.position.line, token.position.column + token.text.len() as i32);
+    ///     Span { start: token.position, end: end }
+    /// });
+    /// let result = many::<Vec<_>, _>(satisfy(|t: Lexeme<&Token, SourcePosition>| t.token.text != "x"))
+    ///     .with(satisfy(|t: Lexeme<&Token, SourcePosition>| t.token.text == "let"))
+    ///     .parse(input);
+    /// assert_eq!(result.map(|x| x.0).map_err(|err| err.position), Err(at(1, 5)));
+    /// # }
+    /// ```
+    pub fn new(slice: &'a [T], locate: F) -> LocatedSlice<'a, T, P, F> {
+        LocatedSlice { slice: slice, index: 0, locate: locate, _marker: PhantomData }
+    }
+
+    ///Returns the tokens which have not yet been taken from the stream
+    pub fn as_slice(&self) -> &'a [T] {
+        &self.slice[self.index..]
+    }
+
+    fn span(&self, index: usize) -> Option<Span<P>> {
+        self.slice.get(index).map(|token| (self.locate)(index, token))
+    }
+}
+
+impl <'a, T> LocatedSlice<'a, T, IndexPosition, fn (usize, &T) -> Span<IndexPosition>> {
+    ///Creates a stream over `slice` where the position of each token is its index in the slice,
+    ///each token spanning from its own index to the index of the token after it
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::lexer::{Lexeme, LocatedSlice};
+    /// # use pc::primitives::IndexPosition;
+    /// # fn main() {
+    /// let tokens = ["a", "b", "c"];
+    /// let result = many::<Vec<_>, _>(satisfy(|t: Lexeme<&&str, IndexPosition>| *t.token != "c"))
+    ///     .with(satisfy(|t: Lexeme<&&str, IndexPosition>| *t.token == "b"))
+    ///     .parse(LocatedSlice::indexed(&tokens[..]));
+    /// assert_eq!(result.map(|x| x.0).map_err(|err| err.position), Err(IndexPosition { index: 2 }));
+    /// # }
+    /// ```
+    pub fn indexed(slice: &'a [T]) -> LocatedSlice<'a, T, IndexPosition, fn (usize, &T) -> Span<IndexPosition>> {
+        fn index<T>(index: usize, _: &T) -> Span<IndexPosition> {
+            Span { start: IndexPosition { index: index }, end: IndexPosition { index: index + 1 } }
+        }
+        LocatedSlice::new(slice, index)
+    }
+}
+
+impl <'a, T, P, F> Stream for LocatedSlice<'a, T, P, F>
+    where T: PartialEq
+        , P: Clone + Ord + Default
+        , F: Fn(usize, &T) -> Span<P> + Clone {
+    type Item = Lexeme<&'a T, P>;
+    type Range = Lexeme<&'a T, P>;
+    fn uncons(self) -> Result<(Lexeme<&'a T, P>, Self), Error<Self::Item, Self::Range>> {
+        match self.slice.get(self.index) {
+            Some(token) => {
+                let span = (self.locate)(self.index, token);
+                Ok((Lexeme { token: token, span: span }, LocatedSlice { index: self.index + 1, .. self }))
+            }
+            None => Err(Error::end_of_input())
+        }
+    }
+    fn update_position(&self, item: &Lexeme<&'a T, P>, position: &mut P) {
+        *position = self.span(self.index).map_or_else(|| item.span.end.clone(), |span| span.start);
+    }
+    fn start_position(&self) -> P {
+        self.span(self.index)
+            .map(|span| span.start)
+            .or_else(|| self.index.checked_sub(1).and_then(|previous| self.span(previous)).map(|span| span.end))
+            .unwrap_or_else(P::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::{Error, Info, Parser, ParseError, SourcePosition, Span};
+    use combinator::{many, many1, satisfy, ParserExt};
+    use char::{char, letter, spaces};
+
+    fn ident() -> ::combinator::Satisfy<TokenStream<::std::vec::IntoIter<Lexeme<String, SourcePosition>>>,
+                                         fn (Lexeme<String, SourcePosition>) -> bool> {
+        fn is_ident(l: Lexeme<String, SourcePosition>) -> bool { l.token != "=" }
+        satisfy(is_ident)
+    }
+
+    fn position(line: i32, column: i32, offset: usize) -> SourcePosition {
+        SourcePosition { line: line, column: column, offset: offset, utf16_column: column }
+    }
+
+    #[test]
+    fn token_positions() {
+        let tokens = lex(many1::<String, _>(letter()).or(char('=').map(|c: char| c.to_string()))
+                             .with_span()
+                             .skip(spaces()),
+                         "a = b\n  c");
+        let lexemes: Vec<_> = tokens.into_iter().unwrap().collect();
+        assert_eq!(lexemes.len(), 4);
+        assert_eq!(lexemes[3], Lexeme { token: "c".to_string(), span: Span {
+            start: position(2, 3, 8),
+            end: position(2, 4, 9)
+        } });
+    }
+
+    #[test]
+    fn error_at_source_position() {
+        let lexemes = vec![
+            Lexeme { token: "a".to_string(), span: Span { start: position(1, 1, 0), end: position(1, 2, 1) } },
+            Lexeme { token: "=".to_string(), span: Span { start: position(1, 5, 4), end: position(1, 6, 5) } },
+        ];
+        let result = many::<Vec<_>, _>(ident()).with(ident()).parse(TokenStream::new(lexemes.clone().into_iter()));
+        assert_eq!(result.map(|x| x.0).map_err(|err| (err.position, err.errors)), Err((position(1, 5, 4), vec![
+            Error::Unexpected(Info::Token(lexemes[1].clone()))
+        ])));
+        //At the end of input the position is the end of the last token
+        let result = many::<Vec<_>, _>(ident()).parse(TokenStream::new(lexemes[..1].to_vec().into_iter()))
+            .and_then(|(_, rest)| {
+                ident().parse_state(State::new(rest)).map(|x| x.0).map_err(|err| err.into_inner())
+            });
+        assert_eq!(result.map_err(|err: ParseError<_>| err.position), Err(position(1, 2, 1)));
+    }
+
+    #[test]
+    fn error_at_first_lexeme() {
+        let tokens = lex(spaces().with(many1::<String, _>(letter()).with_span()), "\n\n  ab");
+        let result = satisfy(|l: Lexeme<String, SourcePosition>| l.token == "=").parse(tokens);
+        assert_eq!(result.map(|x| x.0).map_err(|err| err.position), Err(position(3, 3, 4)));
+    }
+
+    #[test]
+    fn lexer_error_is_reported() {
+        let tokens = lex(many1::<String, _>(letter()).with_span().skip(spaces()), "a b #c");
+        let token = || satisfy(|_: Lexeme<String, SourcePosition>| true);
+        let result = (token(), token(), token()).parse(tokens);
+        let error = result.map(|x| x.0).unwrap_err();
+        assert_eq!(error.position, position(1, 5, 4));
+        let lex_error = match error.errors[..] {
+            [Error::Other(ref err)] => err.to_string(),
+            ref errors => panic!("Expected a lexer error, got {:?}", errors)
+        };
+        assert_eq!(lex_error, "Unexpected token '#'\nExpected letter");
+    }
+
+    #[test]
+    fn lexer_without_progress() {
+        use combinator::any;
+        let tokens = lex(many::<String, _>(letter()).with_span().skip(spaces()), "ab cd 1");
+        let result = many::<Vec<_>, _>(any()).parse(tokens.clone()).map(|(lexemes, _)| lexemes.len());
+        assert_eq!(result, Ok(2));
+        let error = (any(), any(), any()).parse(tokens).map(|x| x.0).unwrap_err();
+        assert_eq!(error.position, position(1, 7, 6));
+        let lex_error = match error.errors[..] {
+            [Error::Other(ref err)] => err.to_string(),
+            ref errors => panic!("Expected a lexer error, got {:?}", errors)
+        };
+        assert_eq!(lex_error, "lexer did not consume any input");
+        //Stopping at the end of the input is not an error
+        let tokens = lex(many::<String, _>(letter()).with_span().skip(spaces()), "ab ");
+        let result = many::<Vec<_>, _>(any()).parse(tokens).map(|(lexemes, rest)| (lexemes.len(), rest.uncons().is_err()));
+        assert_eq!(result, Ok((1, true)));
+    }
+
+    #[test]
+    fn indexed_spans() {
+        use combinator::any;
+        use primitives::IndexPosition;
+        let tokens = ["a", "b"];
+        let result = (any(), any()).parse(LocatedSlice::indexed(&tokens[..])).map(|((_, b), _)| b.span);
+        assert_eq!(result, Ok(Span { start: IndexPosition { index: 1 }, end: IndexPosition { index: 2 } }));
+        //At the end of the slice the position is after the last token
+        let error = (any(), any(), any()).parse(LocatedSlice::indexed(&tokens[..])).map(|x| x.0).unwrap_err();
+        assert_eq!(error.position, IndexPosition { index: 2 });
+    }
+
+    #[test]
+    fn backtracking_lexes_once() {
+        use std::cell::Cell;
+        let count = Rc::new(Cell::new(0));
+        let counter = count.clone();
+        let lexemes = (0..3).map(move |i| {
+            counter.set(counter.get() + 1);
+            Lexeme::<i32, SourcePosition> { token: i, span: Span { start: position(1, i + 1, i as usize), end: position(1, i + 2, i as usize + 1) } }
+        });
+        let token = |n| satisfy(move |l: Lexeme<i32, SourcePosition>| l.token == n);
+        let mut parser = ::combinator::try((token(0), token(1), token(3)))
+            .map(|_| ())
+            .or((token(0), token(1), token(2)).map(|_| ()));
+        assert!(parser.parse(TokenStream::new(lexemes)).is_ok());
+        assert_eq!(count.get(), 3);
+    }
+}
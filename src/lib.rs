@@ -8,7 +8,7 @@
 //!
 //!# Overview
 //!
-//! This library is currently split into three modules.
+//! This library is currently split into eleven modules.
 //!
 //! * `primitives` contains the `Parser` trait as well as various structs dealing with input
 //! streams and errors.
@@ -18,8 +18,37 @@
 //! well as a the `ParserExt` trait which provides a few functions which are more naturally used
 //! through method calls.
 //!
-//! * `char` is the last module. It provides parsers specifically working with streams of
-//! characters. As a few examples it has parsers for accepting digits, letters or whitespace.
+//! * `char` provides parsers specifically working with streams of characters. As a few examples it
+//! has parsers for accepting digits, letters or whitespace as well as integer and floating point
+//! literals.
+//!
+//! * `byte` mirrors the `char` module but provides parsers for streams of bytes. They work on a
+//! `&[u8]` directly as well as on streams which yield bytes by value such as
+//! `primitives::from_slice`.
+//!
+//! * `expression` contains a parser builder which creates expression parsers from a table of
+//! prefix, infix and postfix operators with different precedences and associativities.
+//!
+//! * `memo` contains the `memo` combinator which caches the results of a parser per position in
+//! the input, giving linear time parsing of grammars which backtrack heavily. It also contains
+//! `incremental` which reuses the results of rules when parsing an edited document.
+//!
+//! * `indent` contains parsers for indentation sensitive languages which compare the current
+//! position against a reference position stored in the `IndentStream` wrapper.
+//!
+//! * `lexer` makes it possible to split parsing into two phases, first running a character level
+//! lexer and then parsing the resulting tokens while still reporting positions in the source. It
+//! can also parse a slice of tokens produced by a hand-written lexer.
+//!
+//! * `cst` records named nodes and leaves while parsing into a concrete syntax tree which keeps
+//! all whitespace and comments, making it possible to reproduce the source exactly.
+//!
+//! * `trace` contains the `trace` combinator which logs when a parser is entered and exited as an
+//! indented tree to help debugging grammars. Tracing is only done if the `trace` feature is enabled.
+//!
+//! * `range` is the last module. It provides zero-copy parsers which return slices of the input
+//! directly instead of collecting the parsed items. These parsers work on any stream implementing
+//! `RangeStream` such as `&str` and `&[T]`.
 //!
 //!
 //!# Examples
@@ -97,8 +126,11 @@
 //! }
 //!```
 
+#[cfg(feature = "regex")]
+extern crate regex_automata;
+
 #[doc(inline)]
-pub use primitives::{Parser, ParseError, ParseResult, State, from_iter};
+pub use primitives::{Parser, ParseError, ParseResult, State, from_iter, from_read};
 #[doc(inline)]
 pub use char::{
     char,
@@ -125,10 +157,19 @@ pub use combinator::{
     choice,
     many,
     many1,
+    count,
+    count_min_max,
+    skip_count,
+    skip_count_min_max,
     optional,
     parser,
     satisfy,
     sep_by,
+    sep_by1,
+    sep_end_by,
+    sep_end_by1,
+    end_by,
+    many_till,
     skip_many,
     skip_many1,
     token,
@@ -148,12 +189,45 @@ macro_rules! static_fn {
     } }
 }
 
+//Implements `Resumable` for parsers which do not store any progress, they are resumed by parsing
+//their input again from the start. Any bounds required by the parser type are given in brackets
+//after the type.
+macro_rules! impl_restart {
+    ($($name: ident <$($ty_var: ident),*> $([$($bounds: tt)*])*),+) => {
+        $(
+        impl <$($ty_var),*> ::primitives::Resumable for $name<$($ty_var),*>
+            where $name<$($ty_var),*>: ::primitives::Parser $(, $($bounds)*)* {
+            type PartialState = ();
+            fn parse_resume(&mut self, input: ::primitives::State<Self::Input>, _: &mut ()) -> ::primitives::ParseResult<Self::Output, Self::Input> {
+                ::primitives::Parser::parse_lazy(self, input)
+            }
+        }
+        )+
+    }
+}
+
 ///Module containing the primitive types which is used to create and compose more advanced parsers
 pub mod primitives;
 ///Module containing all specific parsers
 pub mod combinator;
 ///Module containg parsers specialized on character streams
 pub mod char;
+///Module containg parsers specialized on byte streams
+pub mod byte;
+///Module containing zero-copy parsers
+pub mod range;
+///Module containing a parser builder for operator precedence expressions
+pub mod expression;
+///Module containing packrat memoization, left recursive parsers and incremental reparsing
+pub mod memo;
+///Module containing parsers for indentation sensitive languages
+pub mod indent;
+///Module containing stream adapters for parsing the output of a lexer
+pub mod lexer;
+///Module containing a concrete syntax tree builder for lossless parsing
+pub mod cst;
+///Module containing tracing of parsers for debugging grammars
+pub mod trace;
 
 #[cfg(test)]
 mod tests {
@@ -191,6 +265,100 @@ mod tests {
         assert_eq!(result, Ok((123i64, Some(()))));
     }
     #[test]
+    fn buffered_stream() {
+        use std::io::Cursor;
+        let input = primitives::from_read(Cursor::new(&b"1234,56"[..]), 2);
+        let mut p = sep_by::<Vec<Vec<u8>>, _, _>(many1(satisfy(|b: u8| b.is_ascii_digit())), token(b','));
+        assert_eq!(p.parse(input).map(|x| x.0), Ok(vec![b"1234".to_vec(), b"56".to_vec()]));
+    }
+    #[test]
+    fn buffered_stream_backtrack_too_far() {
+        use std::io::Cursor;
+        let input = primitives::from_read(Cursor::new(&b"aaab"[..]), 2);
+        let mut p = try((token(b'a'), token(b'a'), token(b'a'), token(b'a')))
+            .or((token(b'a'), token(b'a'), token(b'a'), token(b'b')));
+        assert!(p.parse(input.clone()).is_err());
+        let err = input.uncons().map(|x| x.0).unwrap_err();
+        assert_eq!(err, Error::Message("Backtracked too far".into()));
+    }
+    #[test]
+    fn buffered_stream_io_error() {
+        use std::io::{self, Read};
+        struct FailingRead;
+        impl Read for FailingRead {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "broken pipe"))
+            }
+        }
+        let err = any().parse(primitives::from_read(FailingRead, 1)).map(|x| x.0).unwrap_err();
+        match err.errors[0] {
+            Error::Other(ref err) => assert_eq!(err.to_string(), "broken pipe"),
+            _ => panic!("Expected an I/O error, got {:?}", err.errors)
+        }
+    }
+    #[test]
+    fn partial_input() {
+        use primitives::PartialParser;
+        let mut p = PartialParser::new((string("let"), spaces(), many1::<String, _>(letter())));
+        p.extend("le".chars());
+        assert_eq!(p.parse(), Ok(None));
+        p.extend("t x".chars());
+        assert_eq!(p.parse(), Ok(None));
+        p.extend(";".chars());
+        assert_eq!(p.parse(), Ok(Some(("let", (), "x".to_string()))));
+        assert_eq!(p.buffered(), 1);
+
+        let mut p = PartialParser::new((string("let"), spaces(), many1::<String, _>(letter())));
+        p.extend("lex".chars());
+        assert!(p.parse().is_err());
+
+        let mut p = PartialParser::new((string("let"), spaces(), many1::<String, _>(letter())));
+        p.extend("le".chars());
+        p.finish();
+        assert!(p.parse().is_err());
+    }
+    #[test]
+    fn partial_input_is_not_parsed_again() {
+        use std::cell::Cell;
+        use primitives::PartialParser;
+        let parsed = Cell::new(0);
+        {
+            let letter = letter().map(|c| {
+                parsed.set(parsed.get() + 1);
+                c
+            });
+            let mut p = PartialParser::new(many::<String, _>(letter).skip(char(';')));
+            p.extend("ab".chars());
+            assert_eq!(p.parse(), Ok(None));
+            p.extend("cd".chars());
+            assert_eq!(p.parse(), Ok(None));
+            p.extend("e;f".chars());
+            assert_eq!(p.parse(), Ok(Some("abcde".to_string())));
+            assert_eq!(p.parse(), Ok(None));
+        }
+        //Each letter is only parsed once even though the frame was received in three chunks
+        assert_eq!(parsed.get(), 6);
+    }
+    #[test]
+    fn partial_input_backtrack_too_far() {
+        use std::cell::RefCell;
+        use primitives::PartialParser;
+        let saved = RefCell::new(None);
+        {
+            let save = parser(|input: State<primitives::PartialStream<char>>| {
+                *saved.borrow_mut() = Some(input.input.clone());
+                Ok(((), Consumed::Empty(input)))
+            });
+            let mut p = PartialParser::new(save.with(char(';')));
+            p.extend(";;".chars());
+            assert_eq!(p.parse(), Ok(Some(';')));
+        }
+        //The parsed `;` was released so the stream can no longer return to it
+        let input = saved.into_inner().unwrap();
+        let err = input.uncons().map(|x| x.0).unwrap_err();
+        assert_eq!(err, Error::Message("Backtracked too far".into()));
+    }
+    #[test]
     fn field() {
         let word = || many(alpha_num());
         let spaces = spaces();
@@ -209,12 +377,99 @@ r"
             .map(|t| t.1)
             .parse_state(State::new(source));
         let state = Consumed::Consumed(State {
-            position: SourcePosition { line: 3, column: 1 },
+            position: SourcePosition { line: 3, column: 1, offset: 5, utf16_column: 1 },
             input: ""
         });
         assert_eq!(result, Ok((123i64, state)));
     }
 
+    #[test]
+    fn source_position_offset() {
+        let source = "\u{e5}\u{1F600}\nx\u{1F600}y";
+        let result = many::<String, _>(satisfy(|c| c != 'y'))
+            .parse_state(State::new(source))
+            .map(|(_, rest)| rest.into_inner().position);
+        let position = SourcePosition { line: 2, column: 3, offset: 12, utf16_column: 4 };
+        assert_eq!(result, Ok(position));
+        assert_eq!(&source[position.offset..], "y");
+    }
+
+    #[test]
+    fn source_stream_policy() {
+        use primitives::{LineEndings, PositionPolicy, SourceStream};
+        let policy = PositionPolicy { tab_width: 4, line_endings: LineEndings::Any };
+        let input = SourceStream::new("a\r\n\tb\rc\n  \td", policy);
+        let mut parser = (char('a'), spaces(), char('b'), spaces(), char('c'), spaces(), parser(|input: State<_>| {
+            let position = input.position;
+            char('d').parse_state(input).map(|(_, rest)| (position, rest))
+        }));
+        let result = parser.parse(input).map(|x| (x.0).6);
+        assert_eq!(result, Ok(SourcePosition { line: 4, column: 5, offset: 11, utf16_column: 4 }));
+    }
+
+    #[test]
+    fn source_stream_range() {
+        use primitives::{LineEndings, PositionPolicy, SourceStream};
+        use range::{range, take_while};
+        let policy = PositionPolicy { tab_width: 8, line_endings: LineEndings::Any };
+        let input = SourceStream::new("ab\r\r\n\tc", policy);
+        let result = take_while(|c: char| c.is_alphabetic())
+            .skip(range("\r"))
+            .skip(take_while(|c: char| c.is_whitespace()))
+            .parse_state(State::new(input))
+            .map(|(_, rest)| rest.into_inner().position);
+        assert_eq!(result, Ok(SourcePosition { line: 3, column: 9, offset: 6, utf16_column: 2 }));
+    }
+
+    #[test]
+    fn stateful_typedef_names() {
+        use combinator::{get_state, modify_state};
+        use primitives::StatefulStream;
+        type Input = StatefulStream<&'static str, Vec<String>>;
+        fn ident(input: State<Input>) -> ParseResult<String, Input> {
+            many1(letter()).skip(spaces()).parse_state(input)
+        }
+        //A declaration `T x;` is only valid if `T` has been declared through a typedef
+        let typedef = string("typedef").skip(spaces()).with(parser(ident)).skip(char(';')).skip(spaces())
+            .then(|name| modify_state(move |types: &mut Vec<String>| types.push(name.clone())))
+            .map(|_| None);
+        let declaration = (parser(ident), get_state()).and_then(|(ty, types): (String, Vec<String>)| {
+                if types.contains(&ty) { Ok(ty) } else { Err(Error::Message("Unknown type".into())) }
+            })
+            .and(parser(ident))
+            .skip(char(';'))
+            .skip(spaces())
+            .map(Some);
+        let mut program = many(try(typedef).or(declaration));
+        let result: Result<(Vec<_>, _), _> = program.parse(StatefulStream::new("typedef T; T x; ", vec![]));
+        assert_eq!(result.map(|(decls, rest)| (decls, rest.into_parts())), Ok((vec![
+            None,
+            Some(("T".to_string(), "x".to_string()))
+        ], ("", vec!["T".to_string()]))));
+        let result: Result<(Vec<_>, _), _> = program.parse(StatefulStream::new("typedef T; U x;", vec![]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn located_slice() {
+        use primitives::{BytePosition, Span};
+        use lexer::{Lexeme, LocatedSlice};
+        let tokens = [(1, 10), (2, 20), (3, 30)];
+        let token = |n| satisfy(move |t: Lexeme<&(i32, usize), BytePosition>| t.token.0 == n);
+        let input = || LocatedSlice::new(&tokens[..], |_, t: &(i32, usize)| {
+            Span { start: BytePosition { position: t.1 }, end: BytePosition { position: t.1 + 2 } }
+        });
+        let result = try((token(1), token(3))).or((token(1), token(2))).map(|(_, t): (_, Lexeme<_, _>)| t.span).parse(input());
+        let span = Span { start: BytePosition { position: 20 }, end: BytePosition { position: 22 } };
+        assert_eq!(result.map(|(p, rest)| (p, rest.as_slice())), Ok((span, &tokens[2..])));
+        //At the end of the slice the position is the end of the last token
+        let result = many::<Vec<_>, _>(token(1).or(token(2)).or(token(3))).with(token(1)).parse(input());
+        assert_eq!(result.map(|x| x.0).map_err(|err| err.position), Err(BytePosition { position: 32 }));
+        //Parsing starts at the position of the first token
+        let result = token(2).parse(input());
+        assert_eq!(result.map(|x| x.0).map_err(|err| err.position), Err(BytePosition { position: 10 }));
+    }
+
     #[derive(Debug, PartialEq)]
     enum Expr {
         Id(String),
@@ -264,7 +519,7 @@ r"
         let result = parser(expr)
             .parse(input);
         let err = ParseError {
-            position: SourcePosition { line: 2, column: 1 },
+            position: SourcePosition { line: 2, column: 1, offset: 1, utf16_column: 1 },
                 errors: vec![
                     Error::Unexpected(','.into()),
                     Error::Expected("integer".into()),
@@ -293,6 +548,85 @@ Expected 'integer', 'identifier', '[' or '('
         assert_eq!(m, expected);
     }
 
+    #[test]
+    fn expression_error_annotated() {
+        let input = "[1,\n\t(2, ,3)\n]";
+        let error = parser(expr)
+            .parse(input)
+            .unwrap_err();
+        let m = format!("{}", error.annotate(input).context(1));
+let expected =
+"Parse error at line: 2, column: 4
+  |
+1 | [1,
+2 | \t(2, ,3)
+  | \t  ^
+3 | ]
+Unexpected token ','
+Expected ')'
+";
+        assert_eq!(m, expected);
+    }
+
+    #[test]
+    fn annotated_error_with_tab_width() {
+        use primitives::{LineEndings, PositionPolicy, SourceStream};
+        let policy = PositionPolicy { tab_width: 4, line_endings: LineEndings::Any };
+        let input = "\tab";
+        let error = (char('\t'), char('a'), char('c'))
+            .parse_state(State::new(SourceStream::new(input, policy)))
+            .map(|_| ())
+            .unwrap_err()
+            .into_inner();
+        let m = format!("{}", error.annotate(input));
+        let expected =
+"Parse error at line: 1, column: 6
+  |
+1 | \tab
+  | \t ^
+Unexpected token 'b'
+Expected 'c'
+";
+        assert_eq!(m, expected);
+    }
+
+    #[test]
+    fn annotated_error_without_offset() {
+        use primitives::SourcePosition;
+        let input = "ab\ncdef";
+        let error: ParseError<&str> = ParseError::new(SourcePosition::new(2, 3), Error::Expected("digit".into()));
+        let m = format!("{}", error.annotate(input));
+        let expected =
+"Parse error at line: 2, column: 3
+  |
+2 | cdef
+  |   ^
+Expected 'digit'
+";
+        assert_eq!(m, expected);
+
+        let error: ParseError<&str> = ParseError::new(SourcePosition::new(0, 1), Error::Expected("digit".into()));
+        let m = format!("{}", error.annotate(input));
+        assert_eq!(m, "Parse error at line: 0, column: 1\nExpected 'digit'\n");
+    }
+
+    #[test]
+    fn annotated_error_end_of_input_with_color() {
+        let input = "1\n";
+        let error = (digit(), newline(), digit())
+            .parse(input)
+            .unwrap_err();
+        let m = format!("{}", error.annotate(input).color(true));
+        let expected = "\x1b[1;31mParse error at line: 2, column: 1\x1b[0m
+\x1b[1;34m  |\x1b[0m
+\x1b[1;34m2 |\x1b[0m 
+\x1b[1;34m  |\x1b[0m \x1b[1;31m^\x1b[0m
+Expected 'digit'
+End of input
+";
+        assert_eq!(m, expected);
+    }
+
     fn term(input: State<&str>) -> ParseResult<Expr, &str> {
         fn times(l: Expr, r: Expr) -> Expr { Expr::Times(Box::new(l), Box::new(r)) }
         fn plus(l: Expr, r: Expr) -> Expr { Expr::Plus(Box::new(l), Box::new(r)) }
@@ -341,11 +675,11 @@ r"
             .or(many1(digit()));
         match p.parse("le123") {
             Ok(_) => assert!(false),
-            Err(err) => assert_eq!(err.position, SourcePosition { line: 1, column: 1 })
+            Err(err) => assert_eq!(err.position, SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 })
         }
         match p.parse("let1") {
             Ok(_) => assert!(false),
-            Err(err) => assert_eq!(err.position, SourcePosition { line: 1, column: 4 })
+            Err(err) => assert_eq!(err.position, SourcePosition { line: 1, column: 4, offset: 3, utf16_column: 4 })
         }
     }
 
@@ -355,7 +689,7 @@ r"
         let err = p.parse("ab,abc")
             .map(|x| format!("{:?}", x))
             .unwrap_err();
-        assert_eq!(err.position, SourcePosition { line: 1, column: 1});
+        assert_eq!(err.position, SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 });
     }
 
     #[test]
@@ -364,7 +698,7 @@ r"
         let err = p.parse("ab")
             .map(|x| format!("{:?}", x))
             .unwrap_err();
-        assert_eq!(err.position, SourcePosition { line: 1, column: 1});
+        assert_eq!(err.position, SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 });
     }
     #[test]
     fn chainl1_error_consume() {
@@ -381,7 +715,7 @@ r"
         let error = result
             .map(|x| format!("{:?}", x))
             .unwrap_err();
-        assert_eq!(error.position, SourcePosition { line: 1, column: 8 });
+        assert_eq!(error.position, SourcePosition { line: 1, column: 8, offset: 7, utf16_column: 8 });
     }
 
     #[test]
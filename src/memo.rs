@@ -0,0 +1,949 @@
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::io::Read;
+use primitives::{BufferedStream, Consumed, Error, Info, IteratorStream, Parser, ParseError, ParseResult,
+                 PartialStream, Positioner, RangeStream, SliceStream, SourcePosition, Span, State, Stream};
+use lexer::{Lexeme, LocatedSlice, TokenStream};
+
+type Position<I> = <<I as Stream>::Item as Positioner>::Position;
+
+type Key<I> = (Position<I>, <I as MemoStream>::Key);
+
+///Streams which can be used with `memo`.
+///Besides the position in the input the result of a parser can depend on state which is carried
+///along with the stream, such as the user state of a `StatefulStream`. `memo_key` returns that
+///state so that results are only reused when both the position and the state are the same.
+pub trait MemoStream: Stream {
+    type Key: Clone + Ord;
+    ///Returns the state of the stream which, together with the position, identifies a result
+    fn memo_key(&self) -> Self::Key;
+}
+
+impl <'a> MemoStream for &'a str {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+impl <'a, T> MemoStream for &'a [T]
+    where T: Positioner {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+impl <'a, T> MemoStream for SliceStream<'a, T>
+    where T: Positioner + Clone {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+impl <I> MemoStream for IteratorStream<I>
+    where I: Iterator + Clone
+        , I::Item: Positioner + Clone {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+impl <'a, T, P, F> MemoStream for LocatedSlice<'a, T, P, F>
+    where T: PartialEq
+        , P: Clone + Ord + Default
+        , F: Fn(usize, &T) -> Span<P> + Clone {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+impl <R> MemoStream for BufferedStream<R>
+    where R: Read {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+impl <T> MemoStream for PartialStream<T>
+    where T: Positioner + Clone {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+impl <I, T, P> MemoStream for TokenStream<I>
+    where I: Iterator<Item=Lexeme<T, P>>
+        , T: Clone + PartialEq
+        , P: Clone + Ord + Default {
+    type Key = ();
+    fn memo_key(&self) { }
+}
+
+///Table which stores the results of memoized parsers for a single parse session.
+///Cloning a `MemoTable` returns a handle to the same underlying table which makes it possible to
+///share one table between several clones of a memoized parser.
+///
+///As results are keyed on the position in the input and the state returned by
+///`MemoStream::memo_key` a table must not be reused when parsing a different input. Create a new
+///table or call `clear` before starting a new parse.
+pub struct MemoTable<I, O>
+    where I: MemoStream {
+    entries: Rc<RefCell<BTreeMap<Key<I>, ParseResult<O, I>>>>
+}
+
+impl <I, O> Clone for MemoTable<I, O>
+    where I: MemoStream {
+    fn clone(&self) -> MemoTable<I, O> {
+        MemoTable { entries: self.entries.clone() }
+    }
+}
+
+impl <I, O> MemoTable<I, O>
+    where I: MemoStream {
+    ///Creates a new empty table
+    pub fn new() -> MemoTable<I, O> {
+        MemoTable { entries: Rc::new(RefCell::new(BTreeMap::new())) }
+    }
+
+    ///Returns the number of results which are currently cached
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    ///Returns true if the table does not contain any cached results
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    ///Removes all cached results from the table
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear()
+    }
+}
+
+impl <I, O> MemoTable<I, O>
+    where I: MemoStream
+        , O: Clone {
+    fn get(&self, key: &Key<I>) -> Option<ParseResult<O, I>> {
+        self.entries.borrow()
+            .get(key)
+            .map(clone_result)
+    }
+
+    fn insert(&self, key: Key<I>, result: &ParseResult<O, I>) {
+        self.entries.borrow_mut()
+            .insert(key, clone_result(result));
+    }
+}
+
+fn clone_result<O, I>(result: &ParseResult<O, I>) -> ParseResult<O, I>
+    where I: Stream
+        , O: Clone {
+    match *result {
+        Ok((ref value, ref rest)) => Ok((value.clone(), rest.clone())),
+        Err(Consumed::Consumed(ref err)) => Err(Consumed::Consumed(clone_error(err))),
+        Err(Consumed::Empty(ref err)) => Err(Consumed::Empty(clone_error(err))),
+        Err(Consumed::Partial(ref err)) => Err(Consumed::Partial(clone_error(err)))
+    }
+}
+
+fn clone_error<I>(error: &ParseError<I>) -> ParseError<I>
+    where I: Stream {
+    let errors = error.errors.iter()
+        .map(|error| {
+            match *error {
+                Error::Unexpected(ref info) => Error::Unexpected(info.clone()),
+                Error::Expected(ref info) => Error::Expected(info.clone()),
+                Error::Message(ref info) => Error::Message(info.clone()),
+                //Arbitrary errors cannot be cloned so only their message is kept
+                Error::Other(ref err) => Error::Message(Info::Owned(err.to_string()))
+            }
+        })
+        .collect();
+    ParseError::from_errors(error.position.clone(), errors)
+}
+
+#[derive(Clone)]
+pub struct Memo<P>
+    where P: Parser
+        , P::Input: MemoStream {
+    parser: P,
+    table: MemoTable<P::Input, P::Output>
+}
+
+impl <P> Parser for Memo<P>
+    where P: Parser
+        , P::Input: MemoStream
+        , P::Output: Clone {
+
+    type Input = P::Input;
+    type Output = P::Output;
+    fn parse_state(&mut self, input: State<P::Input>) -> ParseResult<P::Output, P::Input> {
+        let key = (input.position.clone(), input.input.memo_key());
+        if let Some(result) = self.table.get(&key) {
+            return result
+        }
+        let result = self.parser.parse_state(input);
+        //A partial result only holds until more input is received so it is not stored
+        if let Err(Consumed::Partial(_)) = result {
+            return result
+        }
+        self.table.insert(key, &result);
+        result
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.parser.add_error(errors)
+    }
+}
+
+///Memoizes the results of `p` in `table` (packrat parsing).
+///The first time `p` is run at a position its result is stored in `table` and any later attempt
+///to parse at the same position returns the stored result without running `p` again. This turns
+///grammars which backtrack heavily through `try` and `or` into linear time parsers at the cost of
+///storing one result per position.
+///
+///If the stream carries state, such as the user state of a `StatefulStream`, a result is only
+///reused when the state is also the same as when the result was stored.
+///
+///The table should be created for each parse session and it is possible to share a table
+///between several clones of the memoized parser.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::memo::{memo, MemoTable};
+/// # fn main() {
+/// let table = MemoTable::new();
+/// let number = memo(&table, many1::<String, _>(digit()));
+/// let mut parser = try(number.clone().skip(char('+')).and(number.clone()))
+///     .or(number.map(|n| (n, String::new())));
+/// let result = parser.parse("123");
+/// assert_eq!(result, Ok((("123".to_string(), "".to_string()), "")));
+/// //`many1(digit())` was only run once at the start of the input
+/// assert_eq!(table.len(), 1);
+/// # }
+/// ```
+pub fn memo<P>(table: &MemoTable<P::Input, P::Output>, p: P) -> Memo<P>
+    where P: Parser
+        , P::Input: MemoStream
+        , P::Output: Clone {
+    Memo { parser: p, table: table.clone() }
+}
+
+//The state of a left recursive rule at a position
+enum Seed<I, O>
+    where I: Stream {
+    //The rule is being parsed at the position, `reentered` records if the rule called itself there
+    Growing { seed: Option<(O, Consumed<State<I>>)>, reentered: bool },
+    //The final result of the rule at the position
+    Done(ParseResult<O, I>)
+}
+
+///Table which stores the results of a rule created by `left_recursive`, both the intermediate
+///results while the rule is being grown and the final result at each position.
+///Cloning a `SeedTable` returns a handle to the same underlying table, every instance of a left
+///recursive rule must use the same table so that re-entering the rule at a position can be
+///detected. A table should only be used for a single rule.
+///
+///As with `MemoTable` the results are keyed on the position in the input and the state returned
+///by `MemoStream::memo_key` so a table must not be reused when parsing a different input. Create a
+///new table or call `clear` before starting a new parse.
+pub struct SeedTable<I, O>
+    where I: MemoStream {
+    seeds: Rc<RefCell<BTreeMap<Key<I>, Seed<I, O>>>>
+}
+
+impl <I, O> Clone for SeedTable<I, O>
+    where I: MemoStream {
+    fn clone(&self) -> SeedTable<I, O> {
+        SeedTable { seeds: self.seeds.clone() }
+    }
+}
+
+impl <I, O> SeedTable<I, O>
+    where I: MemoStream {
+    ///Creates a new empty table
+    pub fn new() -> SeedTable<I, O> {
+        SeedTable { seeds: Rc::new(RefCell::new(BTreeMap::new())) }
+    }
+
+    ///Returns the number of positions the rule has been parsed at
+    pub fn len(&self) -> usize {
+        self.seeds.borrow().len()
+    }
+
+    ///Returns true if the table does not contain any results
+    pub fn is_empty(&self) -> bool {
+        self.seeds.borrow().is_empty()
+    }
+
+    ///Removes all results from the table
+    pub fn clear(&self) {
+        self.seeds.borrow_mut().clear()
+    }
+}
+
+#[derive(Clone)]
+pub struct LeftRecursive<P>
+    where P: Parser
+        , P::Input: MemoStream {
+    parser: P,
+    seeds: SeedTable<P::Input, P::Output>
+}
+
+impl <P> Parser for LeftRecursive<P>
+    where P: Parser
+        , P::Input: MemoStream
+        , P::Output: Clone {
+
+    type Input = P::Input;
+    type Output = P::Output;
+    fn parse_state(&mut self, input: State<P::Input>) -> ParseResult<P::Output, P::Input> {
+        let key = (input.position.clone(), input.input.memo_key());
+        {
+            let mut seeds = self.seeds.seeds.borrow_mut();
+            match seeds.get_mut(&key) {
+                Some(&mut Seed::Done(ref result)) => return clone_result(result),
+                //Re-entering the rule at the same position returns the current seed instead of
+                //recursing
+                Some(&mut Seed::Growing { ref seed, ref mut reentered }) => {
+                    *reentered = true;
+                    return match *seed {
+                        Some((ref value, ref rest)) => Ok((value.clone(), rest.clone())),
+                        None => Err(Consumed::Empty(ParseError::empty(input.position)))
+                    }
+                }
+                None => ()
+            }
+            seeds.insert(key.clone(), Seed::Growing { seed: None, reentered: false });
+        }
+        let mut result = self.parser.parse_state(input.clone());
+        let reentered = match self.seeds.seeds.borrow().get(&key) {
+            Some(&Seed::Growing { reentered, .. }) => reentered,
+            _ => false
+        };
+        //Only a rule which called itself at this position can consume more input when it is
+        //given its previous result, so the seed is only grown if that happened
+        if reentered {
+            //Grow the seed for as long as each iteration manages to consume more input
+            loop {
+                let end = match result {
+                    Ok((ref value, ref rest)) => {
+                        let seed = Some((value.clone(), rest.clone()));
+                        self.seeds.seeds.borrow_mut()
+                            .insert(key.clone(), Seed::Growing { seed: seed, reentered: true });
+                        rest.clone().into_inner().position
+                    }
+                    Err(_) => break
+                };
+                let next = self.parser.parse_state(input.clone());
+                match next {
+                    Ok((_, ref rest)) if rest.clone().into_inner().position > end => (),
+                    _ => break
+                }
+                result = next;
+            }
+        }
+        //A partial result only holds until more input is received so it is not stored
+        if let Err(Consumed::Partial(_)) = result {
+            self.seeds.seeds.borrow_mut().remove(&key);
+        }
+        else {
+            self.seeds.seeds.borrow_mut().insert(key, Seed::Done(clone_result(&result)));
+        }
+        result
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.parser.add_error(errors)
+    }
+}
+
+///Creates a parser from the rule `p` which may refer to itself in the leftmost position, something
+///which would otherwise recurse forever.
+///The recursive references in `p` must also be created with `left_recursive` and `seeds`. When the
+///rule is re-entered at the same position the recursive call fails at first and `p` is then run
+///repeatedly, each time letting the recursive call return the previous result, for as long as
+///this lets `p` consume more input.
+///
+///The final result of the rule at each position is kept in `seeds` so, as with `memo`, the rule is
+///only parsed once per position for the whole parse.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::memo::{left_recursive, SeedTable};
+/// # fn main() {
+/// fn number(input: State<&str>) -> ParseResult<i32, &str> {
+///     many1(digit())
+///         .map(|s: String| s.parse::<i32>().unwrap())
+///         .parse_state(input)
+/// }
+/// //expr = expr '-' number | number
+/// fn expr<'a>(seeds: &SeedTable<&'a str, i32>, input: State<&'a str>) -> ParseResult<i32, &'a str> {
+///     left_recursive(seeds, parser(|input| {
+///         try(parser(|input| expr(seeds, input)).skip(char('-')).and(parser(number)))
+///             .map(|(l, r)| l - r)
+///             .or(parser(number))
+///             .parse_state(input)
+///     }))
+///     .parse_state(input)
+/// }
+/// let seeds = SeedTable::new();
+/// let result = parser(|input| expr(&seeds, input)).parse("10-2-3");
+/// assert_eq!(result, Ok((5, "")));
+/// # }
+/// ```
+pub fn left_recursive<P>(seeds: &SeedTable<P::Input, P::Output>, p: P) -> LeftRecursive<P>
+    where P: Parser
+        , P::Input: MemoStream
+        , P::Output: Clone {
+    LeftRecursive { parser: p, seeds: seeds.clone() }
+}
+
+///An edit of a document where the bytes `start..end` of the old text were replaced by `len` bytes
+///of new text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Edit {
+    ///The byte offset in the old text where the edit starts
+    pub start: usize,
+    ///The byte offset in the old text where the edit ends
+    pub end: usize,
+    ///The length in bytes of the text which replaced `start..end`
+    pub len: usize
+}
+
+impl Edit {
+    ///Creates an edit replacing the bytes `start..end` with `len` bytes
+    pub fn new(start: usize, end: usize, len: usize) -> Edit {
+        assert!(start <= end, "Edit::new: start must not be after end");
+        Edit { start: start, end: end, len: len }
+    }
+}
+
+struct Entry<O> {
+    value: O,
+    //The number of bytes the rule consumed
+    len: usize,
+    //The byte offset just after the last character the rule looked at, including lookahead
+    examined: usize
+}
+
+///Cache of the results of a rule created by `incremental`, keyed on the byte offset the rule was
+///run at. Each rule needs its own cache.
+///When the document is edited `edit` keeps every result which could not have been affected by the
+///edit, so that parsing the new text only has to run the rules which overlap the edit.
+///
+///Results which are not removed by an edit are kept until they are removed with `retain` or
+///`clear`, for instance the results past the end of a document which got shorter.
+///Cloning an `IncrementalCache` returns a handle to the same underlying cache.
+pub struct IncrementalCache<O> {
+    entries: Rc<RefCell<BTreeMap<usize, Entry<O>>>>
+}
+
+impl <O> Clone for IncrementalCache<O> {
+    fn clone(&self) -> IncrementalCache<O> {
+        IncrementalCache { entries: self.entries.clone() }
+    }
+}
+
+impl <O> IncrementalCache<O> {
+    ///Creates a new empty cache
+    pub fn new() -> IncrementalCache<O> {
+        IncrementalCache { entries: Rc::new(RefCell::new(BTreeMap::new())) }
+    }
+
+    ///Returns the number of cached results
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    ///Returns true if the cache does not contain any results
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    ///Removes all cached results
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear()
+    }
+
+    ///Keeps only the results for which `keep` returns true when given the byte offset of the
+    ///result and the result itself
+    pub fn retain<F>(&self, mut keep: F)
+        where F: FnMut(usize, &O) -> bool {
+        let mut entries = self.entries.borrow_mut();
+        let old = ::std::mem::replace(&mut *entries, BTreeMap::new());
+        entries.extend(old.into_iter().filter(|&(offset, ref entry)| keep(offset, &entry.value)));
+    }
+
+    ///Updates the cache after the document was changed by `edit`.
+    ///Results which only looked at text before the edit are kept, results which start after the
+    ///edit are moved to their new offset and every other result is removed.
+    ///Must be called on the cache of every rule.
+    pub fn edit(&self, edit: Edit) {
+        let mut entries = self.entries.borrow_mut();
+        let old = ::std::mem::replace(&mut *entries, BTreeMap::new());
+        for (offset, mut entry) in old {
+            if entry.examined <= edit.start {
+                entries.insert(offset, entry);
+            }
+            else if offset >= edit.end {
+                let shift = |n: usize| n - edit.end + edit.start + edit.len;
+                entry.examined = shift(entry.examined);
+                entries.insert(shift(offset), entry);
+            }
+        }
+    }
+}
+
+///Wrapper around a character stream which keeps track of the byte offset into the document and
+///how far ahead the rules created by `incremental` have looked.
+pub struct IncrementalStream<I> {
+    input: I,
+    offset: usize,
+    examined: Rc<Cell<usize>>
+}
+
+impl <I> Clone for IncrementalStream<I>
+    where I: Clone {
+    fn clone(&self) -> IncrementalStream<I> {
+        IncrementalStream { input: self.input.clone(), offset: self.offset, examined: self.examined.clone() }
+    }
+}
+
+impl <I> ::std::fmt::Debug for IncrementalStream<I>
+    where I: ::std::fmt::Debug {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "IncrementalStream {{ input: {:?}, offset: {} }}", self.input, self.offset)
+    }
+}
+
+impl <I> IncrementalStream<I>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str> {
+    ///Wraps `input`, the full text of the document, so that it can be parsed with the rules
+    ///created by `incremental`
+    pub fn new(input: I) -> IncrementalStream<I> {
+        IncrementalStream { input: input, offset: 0, examined: Rc::new(Cell::new(0)) }
+    }
+}
+
+impl <I> IncrementalStream<I> {
+    ///Returns the byte offset of the stream from the start of the document
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    ///Returns the wrapped stream
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+fn look_at(examined: &Cell<usize>, end: usize) {
+    examined.set(cmp::max(examined.get(), end));
+}
+
+impl <I> MemoStream for IncrementalStream<I>
+    where I: MemoStream<Item=char>
+        , I::Range: AsRef<str> {
+    type Key = I::Key;
+    fn memo_key(&self) -> I::Key {
+        self.input.memo_key()
+    }
+}
+
+impl <I> Stream for IncrementalStream<I>
+    where I: Stream<Item=char>
+        , I::Range: AsRef<str> {
+    type Item = char;
+    type Range = I::Range;
+    fn uncons(self) -> Result<(char, IncrementalStream<I>), Error<char, I::Range>> {
+        match self.input.uncons() {
+            Ok((c, input)) => {
+                let offset = self.offset + c.len_utf8();
+                look_at(&self.examined, offset);
+                Ok((c, IncrementalStream { input: input, offset: offset, examined: self.examined }))
+            }
+            Err(err) => {
+                //Seeing the end of the input counts as looking past the last character as text
+                //appended at the end could change the result
+                look_at(&self.examined, self.offset + 1);
+                Err(err)
+            }
+        }
+    }
+    fn update_position(&self, item: &char, position: &mut SourcePosition) {
+        self.input.update_position(item, position)
+    }
+    fn update_range_position(&self, range: &I::Range, position: &mut SourcePosition) {
+        self.input.update_range_position(range, position)
+    }
+    fn is_partial(&self) -> bool {
+        self.input.is_partial()
+    }
+    fn start_position(&self) -> SourcePosition {
+        self.input.start_position()
+    }
+}
+
+impl <I> RangeStream for IncrementalStream<I>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str> {
+    fn uncons_range(self, size: usize) -> Result<(I::Range, IncrementalStream<I>), Error<char, I::Range>> {
+        match self.input.uncons_range(size) {
+            Ok((range, input)) => {
+                let offset = self.offset + range.as_ref().len();
+                look_at(&self.examined, offset);
+                Ok((range, IncrementalStream { input: input, offset: offset, examined: self.examined }))
+            }
+            Err(err) => {
+                look_at(&self.examined, self.offset + size);
+                Err(err)
+            }
+        }
+    }
+    fn uncons_while<F>(self, predicate: F) -> Result<(I::Range, IncrementalStream<I>), Error<char, I::Range>>
+        where F: FnMut(char) -> bool {
+        let (range, input) = try!(self.input.uncons_while(predicate));
+        let offset = self.offset + range.as_ref().len();
+        //The character after the range was looked at as well
+        look_at(&self.examined, offset + 1);
+        Ok((range, IncrementalStream { input: input, offset: offset, examined: self.examined }))
+    }
+}
+
+#[derive(Clone)]
+pub struct Incremental<P>
+    where P: Parser {
+    parser: P,
+    cache: IncrementalCache<P::Output>
+}
+
+impl <I, P> Parser for Incremental<P>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str>
+        , P: Parser<Input=IncrementalStream<I>>
+        , P::Output: Clone {
+
+    type Input = IncrementalStream<I>;
+    type Output = P::Output;
+    fn parse_state(&mut self, input: State<IncrementalStream<I>>) -> ParseResult<P::Output, IncrementalStream<I>> {
+        let offset = input.input.offset;
+        let examined_cell = input.input.examined.clone();
+        let cached = self.cache.entries.borrow()
+            .get(&offset)
+            .map(|entry| (entry.value.clone(), entry.len, entry.examined));
+        if let Some((value, len, examined)) = cached {
+            //Skip over the text the rule consumed last time, updating the position for the new text
+            look_at(&examined_cell, examined);
+            let (_, rest) = try!(input.uncons_range(len));
+            return Ok((value, if len == 0 { rest.as_empty() } else { rest }))
+        }
+        let previous = examined_cell.get();
+        examined_cell.set(offset);
+        let result = self.parser.parse_state(input);
+        let examined = examined_cell.get();
+        examined_cell.set(cmp::max(previous, examined));
+        if let Ok((ref value, ref rest)) = result {
+            let len = match *rest {
+                Consumed::Consumed(ref rest) | Consumed::Empty(ref rest) | Consumed::Partial(ref rest) => {
+                    rest.input.offset - offset
+                }
+            };
+            let entry = Entry { value: value.clone(), len: len, examined: examined };
+            self.cache.entries.borrow_mut().insert(offset, entry);
+        }
+        result
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.parser.add_error(errors)
+    }
+}
+
+///Caches the successful results of the rule `p` in `cache` so that the rule does not need to be
+///run again when parsing an edited document, as long as the edit does not touch any of the text
+///the rule looked at. Failed parses are never cached so a rule which failed at an offset is run
+///again every time it is tried there.
+///The input must be wrapped in an `IncrementalStream` and each rule must be given its own cache.
+///
+///Since reused results are only moved to their new offset they should not contain positions, if
+///the position of a rule is needed it should be recorded relative to the start of the rule.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::memo::{incremental, Edit, IncrementalCache, IncrementalStream};
+/// # use std::cell::Cell;
+/// # use std::rc::Rc;
+/// # fn main() {
+/// let runs = Rc::new(Cell::new(0));
+/// let counter = runs.clone();
+/// let cache = IncrementalCache::new();
+/// let number = incremental(&cache, many1::<String, _>(digit()).skip(spaces())
+///     .map(move |s| { counter.set(counter.get() + 1); s.parse::<i32>().unwrap() }));
+/// let mut numbers = many::<Vec<_>, _>(number);
+///
+/// let result = numbers.parse(IncrementalStream::new("1 22 333 4444")).map(|x| x.0);
+/// assert_eq!(result, Ok(vec![1, 22, 333, 4444]));
+/// assert_eq!(runs.get(), 4);
+///
+/// //Replace "22" with "5", only the numbers which looked at the edited text are parsed again
+/// cache.edit(Edit::new(2, 4, 1));
+/// let result = numbers.parse(IncrementalStream::new("1 5 333 4444")).map(|x| x.0);
+/// assert_eq!(result, Ok(vec![1, 5, 333, 4444]));
+/// assert_eq!(runs.get(), 6);
+/// # }
+/// ```
+pub fn incremental<P>(cache: &IncrementalCache<P::Output>, p: P) -> Incremental<P>
+    where P: Parser {
+    Incremental { parser: p, cache: cache.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use primitives::{Consumed, Error, Parser, ParseError, ParseResult, SourcePosition, State, StatefulStream};
+    use combinator::{between, get_state, many1, parser, put_state, try, ParserExt};
+    use char::{char, digit};
+
+    #[test]
+    fn memo_runs_parser_once_per_position() {
+        let calls = Rc::new(Cell::new(0));
+        let table = MemoTable::new();
+        let counter = calls.clone();
+        let digit_counted = memo(&table, parser(move |input: State<&'static str>| {
+            counter.set(counter.get() + 1);
+            digit().parse_state(input)
+        }));
+        let mut p = try(digit_counted.clone().skip(char('a')))
+            .or(try(digit_counted.clone().skip(char('b'))))
+            .or(digit_counted.skip(char('c')));
+        assert_eq!(p.parse("1c"), Ok(('1', "")));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn memo_is_keyed_on_user_state() {
+        let table = MemoTable::new();
+        let state_digit = memo(&table, get_state().and(digit()));
+        let mut p = try(put_state(1).with(state_digit.clone()).skip(char('x')))
+            .or(put_state(2).with(state_digit));
+        let result = p.parse(StatefulStream::new("5", 0)).map(|x| x.0);
+        assert_eq!(result, Ok((2, '5')));
+        assert_eq!(table.len(), 2);
+    }
+
+    fn number(input: State<&'static str>) -> ParseResult<i32, &'static str> {
+        many1(digit())
+            .map(|s: String| s.parse::<i32>().unwrap())
+            .parse_state(input)
+    }
+
+    //expr = expr '-' number | number
+    fn sub_expr(seeds: &SeedTable<&'static str, i32>, input: State<&'static str>) -> ParseResult<i32, &'static str> {
+        left_recursive(seeds, parser(|input| {
+            try(parser(|input| sub_expr(seeds, input)).skip(char('-')).and(parser(number)))
+                .map(|(l, r)| l - r)
+                .or(parser(number))
+                .parse_state(input)
+        }))
+        .parse_state(input)
+    }
+
+    fn parse_sub_expr(input: &'static str) -> Result<(i32, &'static str), ParseError<&'static str>> {
+        let seeds = SeedTable::new();
+        parser(|input| sub_expr(&seeds, input)).parse(input)
+    }
+
+    #[test]
+    fn left_recursive_is_left_associative() {
+        assert_eq!(parse_sub_expr("10-2-3-1"), Ok((4, "")));
+        assert_eq!(parse_sub_expr("7"), Ok((7, "")));
+        assert_eq!(parse_sub_expr("7-"), Ok((7, "-")));
+    }
+
+    //expr = expr '-' term | term, term = '(' expr ')' | number
+    fn nested_expr(seeds: &SeedTable<&'static str, i32>, input: State<&'static str>) -> ParseResult<i32, &'static str> {
+        let term = || between(char('('), char(')'), parser(move |input| nested_expr(seeds, input)))
+            .or(parser(number));
+        left_recursive(seeds, parser(|input| {
+            try(parser(|input| nested_expr(seeds, input)).skip(char('-')).and(term()))
+                .map(|(l, r)| l - r)
+                .or(term())
+                .parse_state(input)
+        }))
+        .parse_state(input)
+    }
+
+    #[test]
+    fn left_recursive_nested() {
+        let seeds = SeedTable::new();
+        let result = parser(|input| nested_expr(&seeds, input)).parse("10-(5-2)-(1)");
+        assert_eq!(result, Ok((6, "")));
+    }
+
+    #[test]
+    fn left_recursive_deep_nesting() {
+        //expr = expr '-' term | term, term = '(' expr ')' | number
+        fn counted_expr(seeds: &SeedTable<&'static str, i32>, calls: &Cell<i32>, input: State<&'static str>) -> ParseResult<i32, &'static str> {
+            let term = || between(char('('), char(')'), parser(move |input| counted_expr(seeds, calls, input)))
+                .or(parser(number));
+            left_recursive(seeds, parser(|input| {
+                calls.set(calls.get() + 1);
+                try(parser(|input| counted_expr(seeds, calls, input)).skip(char('-')).and(term()))
+                    .map(|(l, r)| l - r)
+                    .or(term())
+                    .parse_state(input)
+            }))
+            .parse_state(input)
+        }
+        let input = "((((((((((((((((1))))))))))))))))";
+        let seeds = SeedTable::new();
+        let calls = Cell::new(0);
+        let result = parser(|input| counted_expr(&seeds, &calls, input)).parse(input);
+        assert_eq!(result, Ok((1, "")));
+        //The rule is re-entered at every position so it is grown once, after that each nested
+        //rule is found in the table
+        assert_eq!(calls.get(), 2 * 17);
+    }
+
+    #[test]
+    fn left_recursive_stateful_rule() {
+        //A rule which is an `FnMut` closure rather than a function
+        let calls = Rc::new(Cell::new(0));
+        let seeds = SeedTable::new();
+        let counter = calls.clone();
+        let mut expr = left_recursive(&seeds, parser(move |input: State<&'static str>| {
+            counter.set(counter.get() + 1);
+            parser(number).parse_state(input)
+        }));
+        assert_eq!(expr.parse("12"), Ok((12, "")));
+        //The rule never calls itself so it is not grown
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn left_recursive_error() {
+        let result = parse_sub_expr("a");
+        assert_eq!(result.map_err(|err| err.position), Err(SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 }));
+    }
+
+    #[test]
+    fn memo_caches_errors() {
+        let calls = Rc::new(Cell::new(0));
+        let table = MemoTable::new();
+        let counter = calls.clone();
+        let mut p = memo(&table, parser(move |input: State<&'static str>| {
+            counter.set(counter.get() + 1);
+            digit().parse_state(input)
+        }));
+        let first = p.parse_state(State::new("a"));
+        let second = p.parse_state(State::new("a"));
+        assert!(first.is_err());
+        assert_eq!(first, second);
+        assert_eq!(calls.get(), 1);
+        table.clear();
+        assert!(table.is_empty());
+        let _ = p.parse_state(State::new("a"));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn memo_other_error_is_kept_as_message() {
+        use std::fmt;
+        use std::error::Error as StdError;
+        #[derive(Debug)]
+        struct Failure;
+        impl fmt::Display for Failure {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "failure")
+            }
+        }
+        impl StdError for Failure {
+            fn description(&self) -> &str { "failure" }
+        }
+        fn fail(input: State<&'static str>) -> ParseResult<char, &'static str> {
+            Err(Consumed::Empty(ParseError::new(input.position, Failure.into())))
+        }
+        let table = MemoTable::new();
+        let mut p = memo(&table, parser(fail));
+        let _ = p.parse("");
+        let result = p.parse("");
+        assert_eq!(result, Err(ParseError {
+            position: SourcePosition { line: 1, column: 1, offset: 0, utf16_column: 1 },
+            errors: vec![Error::Message("failure".into())]
+        }));
+    }
+
+    fn counted_word(cache: &IncrementalCache<String>, runs: &Rc<Cell<i32>>)
+        -> Incremental<::combinator::Map<::combinator::Many1<String, ::char::Letter<IncrementalStream<&'static str>>>,
+                                         Box<Fn(String) -> String>>> {
+        let counter = runs.clone();
+        let f: Box<Fn(String) -> String> = Box::new(move |s| { counter.set(counter.get() + 1); s });
+        incremental(cache, many1(::char::letter()).map(f))
+    }
+
+    #[test]
+    fn incremental_reuses_nested_rules() {
+        use combinator::{many, sep_by};
+        use char::{char, spaces};
+        let runs = Rc::new(Cell::new(0));
+        let words = IncrementalCache::new();
+        let lines = IncrementalCache::new();
+        let line = incremental(&lines, sep_by::<Vec<_>, _, _>(counted_word(&words, &runs), char(' ')).skip(char('\n')));
+        let mut document = many::<Vec<_>, _>(line).skip(spaces());
+
+        let result = document.parse_state(State::new(IncrementalStream::new("ab cd\nef\ngh ij\n")))
+            .map(|(lines, _)| lines.len());
+        assert_eq!(result, Ok(3));
+        assert_eq!(runs.get(), 5);
+
+        //Insert "x" at the start of the second line, the first line is kept, the last line is
+        //moved and only "ef" is parsed again
+        words.edit(Edit::new(6, 6, 1));
+        lines.edit(Edit::new(6, 6, 1));
+        let (result, rest) = document.parse_state(State::new(IncrementalStream::new("ab cd\nxef\ngh ij\n"))).unwrap();
+        assert_eq!(result, vec![
+            vec!["ab".to_string(), "cd".to_string()],
+            vec!["xef".to_string()],
+            vec!["gh".to_string(), "ij".to_string()]
+        ]);
+        assert_eq!(runs.get(), 6);
+        let rest = rest.into_inner();
+        assert_eq!(rest.position, SourcePosition { line: 4, column: 1, offset: 16, utf16_column: 1 });
+        assert_eq!(rest.input.offset(), 16);
+    }
+
+    #[test]
+    fn incremental_edit_at_end_of_input() {
+        let runs = Rc::new(Cell::new(0));
+        let cache = IncrementalCache::new();
+        let mut word = counted_word(&cache, &runs);
+        assert_eq!(word.parse(IncrementalStream::new("ab")).map(|x| x.0), Ok("ab".to_string()));
+        //The rule saw the end of the input so appending text must run it again
+        cache.edit(Edit::new(2, 2, 1));
+        assert_eq!(word.parse(IncrementalStream::new("abc")).map(|x| x.0), Ok("abc".to_string()));
+        assert_eq!(runs.get(), 2);
+        //Deleting text which the rule never looked at keeps the result
+        cache.edit(Edit::new(0, 0, 2));
+        cache.edit(Edit::new(0, 2, 0));
+        assert_eq!(word.parse(IncrementalStream::new("abc")).map(|x| x.0), Ok("abc".to_string()));
+        assert_eq!(runs.get(), 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn incremental_retain() {
+        use combinator::sep_by;
+        let runs = Rc::new(Cell::new(0));
+        let cache = IncrementalCache::new();
+        let mut words = sep_by::<Vec<_>, _, _>(counted_word(&cache, &runs), char(' '));
+        assert_eq!(words.parse(IncrementalStream::new("ab cd")).map(|x| x.0.len()), Ok(2));
+        //Replacing the space joins the words, the result for "cd" is moved but no longer used
+        cache.edit(Edit::new(2, 3, 1));
+        assert_eq!(words.parse(IncrementalStream::new("abxcd")).map(|x| x.0.len()), Ok(1));
+        assert_eq!(runs.get(), 3);
+        assert_eq!(cache.len(), 2);
+        cache.retain(|_, word| word != "cd");
+        assert_eq!(cache.len(), 1);
+        assert_eq!(words.parse(IncrementalStream::new("abxcd")).map(|x| x.0.len()), Ok(1));
+        assert_eq!(runs.get(), 3);
+    }
+}
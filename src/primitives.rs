@@ -1,6 +1,11 @@
 use std::fmt;
 use std::error::Error as StdError;
 use std::any::Any;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::rc::Rc;
+use memo::MemoStream;
 
 ///Struct which represents a position in a source file
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
@@ -8,11 +13,31 @@ pub struct SourcePosition {
     ///Current line of the input
     pub line: i32,
     ///Current column of the input
-    pub column: i32
+    pub column: i32,
+    ///Current byte offset from the start of the input
+    pub offset: usize,
+    ///Current column of the input counted in UTF-16 code units, as used by for instance the
+    ///language server protocol
+    pub utf16_column: i32
+}
+
+impl SourcePosition {
+    ///Creates a position at `line` and `column` for positions which are not tied to an offset in
+    ///the input. The byte offset is 0 and the UTF-16 column is the same as `column`.
+    pub fn new(line: i32, column: i32) -> SourcePosition {
+        SourcePosition { line: line, column: column, offset: 0, utf16_column: column }
+    }
+}
+
+impl Default for SourcePosition {
+    ///The start of the input, line 1 and column 1
+    fn default() -> SourcePosition {
+        SourcePosition::new(1, 1)
+    }
 }
 
 ///Struct which represents a position in a byte stream
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
 pub struct BytePosition {
     ///Current position
     pub position: usize
@@ -24,6 +49,37 @@ impl fmt::Display for BytePosition {
     }
 }
 
+///Struct which represents the index of an item in a sequence of items which do not have any other
+///position, such as the tokens in `lexer::LocatedSlice::indexed`.
+///As the items do not provide a position type themselves `IndexPosition` is also its own
+///`Positioner`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub struct IndexPosition {
+    ///The index of the item
+    pub index: usize
+}
+
+impl fmt::Display for IndexPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "index: {}", self.index)
+    }
+}
+
+///Struct which represents the part of the input between two positions
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Span<P> {
+    ///The position of the first item in the span
+    pub start: P,
+    ///The position directly after the last item in the span
+    pub end: P
+}
+
+impl <P: fmt::Display> fmt::Display for Span<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} - {}", self.start, self.end)
+    }
+}
+
 ///Enum holding error information
 ///As there is implementations of `From` for `T: Positioner`, `String` and `&'static str` the
 ///constructor need not be used directly as calling `msg.into()` should turn a message into the
@@ -65,6 +121,11 @@ impl <R> From<char> for Info<char, R> {
         Info::Token(s)
     }
 }
+impl <R> From<u8> for Info<u8, R> {
+    fn from(s: u8) -> Info<u8, R> {
+        Info::Token(s)
+    }
+}
 impl <T, R> From<String> for Info<T, R> {
     fn from(s: String) -> Info<T, R> {
         Info::Owned(s)
@@ -119,7 +180,12 @@ pub enum Consumed<T> {
     ///Constructor indicating that the parser has consumed elements
     Consumed(T),
     ///Constructor indicating that the parser did not consume any elements
-    Empty(T)
+    Empty(T),
+    ///Constructor indicating that the parser reached the end of a partial stream (see
+    ///`Stream::is_partial`) and needs more input before it can finish.
+    ///Only used for errors, parsers propagate it like an error which consumed input as it is not
+    ///yet known if the parser fails.
+    Partial(T)
 }
 
 impl <T> Consumed<T> {
@@ -128,7 +194,15 @@ impl <T> Consumed<T> {
     pub fn is_empty(&self) -> bool {
         match *self {
             Consumed::Empty(_) => true,
-            Consumed::Consumed(_) => false
+            Consumed::Consumed(_) | Consumed::Partial(_) => false
+        }
+    }
+
+    ///Returns true if `self` is partial
+    pub fn is_partial(&self) -> bool {
+        match *self {
+            Consumed::Partial(_) => true,
+            Consumed::Consumed(_) | Consumed::Empty(_) => false
         }
     }
 
@@ -136,18 +210,27 @@ impl <T> Consumed<T> {
     pub fn into_inner(self) -> T {
         match self {
             Consumed::Empty(x) => x,
-            Consumed::Consumed(x) => x
+            Consumed::Consumed(x) => x,
+            Consumed::Partial(x) => x
         }
     }
 
-    ///Converts `self` into the Consumed state
+    ///Converts `self` into the Consumed state.
+    ///A partial value stays partial.
     pub fn as_consumed(self) -> Consumed<T> {
-        Consumed::Consumed(self.into_inner())
+        match self {
+            Consumed::Partial(x) => Consumed::Partial(x),
+            x => Consumed::Consumed(x.into_inner())
+        }
     }
 
-    ///Converts `self` into theEmpty state
+    ///Converts `self` into the Empty state.
+    ///A partial value stays partial as it is not yet known if the parser fails.
     pub fn as_empty(self) -> Consumed<T> {
-        Consumed::Empty(self.into_inner())
+        match self {
+            Consumed::Partial(x) => Consumed::Partial(x),
+            x => Consumed::Empty(x.into_inner())
+        }
     }
 
     ///Maps over the contained value without changing the consumed state
@@ -155,7 +238,8 @@ impl <T> Consumed<T> {
         where F: FnOnce(T) -> U {
         match self {
             Consumed::Empty(x) => Consumed::Empty(f(x)),
-            Consumed::Consumed(x) => Consumed::Consumed(f(x))
+            Consumed::Consumed(x) => Consumed::Consumed(f(x)),
+            Consumed::Partial(x) => Consumed::Partial(f(x))
         }
     }
 
@@ -195,7 +279,7 @@ impl <T> Consumed<T> {
         where F: FnOnce(T) -> ParseResult<U, I>
             , I: Stream {
         match self {
-            Consumed::Consumed(x) => {
+            Consumed::Consumed(x) | Consumed::Partial(x) => {
                 match f(x) {
                     Ok((v, Consumed::Empty(rest))) => Ok((v, Consumed::Consumed(rest))),
                     Err(Consumed::Empty(err)) => Err(Consumed::Consumed(err)),
@@ -300,7 +384,15 @@ impl <S> fmt::Display for ParseError<S>
         , <S::Item as Positioner>::Position: fmt::Display {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         try!(writeln!(f, "Parse error at {}", self.position));
+        self.write_messages(f)
+    }
+}
 
+impl <S> ParseError<S>
+    where S: Stream
+        , S::Item: fmt::Display
+        , S::Range: fmt::Display {
+    fn write_messages(&self, f: &mut fmt::Formatter) -> fmt::Result {
         //First print the token that we did not expect
         //There should really just be one unexpected message at this point though we print them
         //all to be safe
@@ -346,6 +438,111 @@ impl <S> fmt::Display for ParseError<S>
         Ok(())
     }
 }
+
+impl <S> ParseError<S>
+    where S: Stream<Item=char> {
+    ///Returns a value which displays this error together with the line of `source` it occured on
+    ///and a caret pointing at the column of the error.
+    ///`source` should be the full text which was parsed to produce this error.
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # fn main() {
+    /// let error = (string("let y = "), digit()).parse("let y = ;").unwrap_err();
+    /// let expected = "Parse error at line: 1, column: 9
+    ///   |
+    /// 1 | let y = ;
+    ///   |         ^
+    /// Unexpected token ';'
+    /// Expected 'digit'
+    /// ";
+    /// assert_eq!(error.annotate("let y = ;").to_string(), expected);
+    /// # }
+    /// ```
+    pub fn annotate<'a>(&'a self, source: &'a str) -> AnnotatedError<'a, S> {
+        AnnotatedError { error: self, source: source, context: 0, color: false }
+    }
+}
+
+///Displays a `ParseError` together with the source text it occured in.
+///Created by `ParseError::annotate`.
+pub struct AnnotatedError<'a, S>
+    where S: Stream<Item=char> + 'a {
+    error: &'a ParseError<S>,
+    source: &'a str,
+    context: usize,
+    color: bool
+}
+
+impl <'a, S> AnnotatedError<'a, S>
+    where S: Stream<Item=char> {
+    ///Sets the number of lines to display before and after the line containing the error
+    pub fn context(mut self, lines: usize) -> AnnotatedError<'a, S> {
+        self.context = lines;
+        self
+    }
+
+    ///If `color` is true the output is highlighted using ANSI escape codes
+    pub fn color(mut self, color: bool) -> AnnotatedError<'a, S> {
+        self.color = color;
+        self
+    }
+
+    fn style(&self, code: &'static str) -> &'static str {
+        if self.color { code } else { "" }
+    }
+}
+
+impl <'a, S> fmt::Display for AnnotatedError<'a, S>
+    where S: Stream<Item=char>
+        , S::Range: fmt::Display {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const RED: &'static str = "\x1b[1;31m";
+        const BLUE: &'static str = "\x1b[1;34m";
+        const RESET: &'static str = "\x1b[0m";
+        let position = self.error.position;
+        try!(writeln!(f, "{}Parse error at {}{}", self.style(RED), position, self.style(RESET)));
+
+        let lines: Vec<&str> = self.source.split('\n')
+            .map(|line| line.trim_right_matches('\r'))
+            .collect();
+        if position.line < 1 || position.line as usize > lines.len() {
+            //The position does not refer to a line in the source so only the messages are shown
+            return self.error.write_messages(f)
+        }
+        let error_line = (position.line - 1) as usize;
+        let first = error_line.saturating_sub(self.context);
+        let last = ::std::cmp::min(error_line + self.context, lines.len().saturating_sub(1));
+        let width = (::std::cmp::max(last, error_line) + 1).to_string().len();
+
+        try!(writeln!(f, "{}{:w$} |{}", self.style(BLUE), "", self.style(RESET), w = width));
+        for index in first..(::std::cmp::max(last, error_line) + 1) {
+            let line = lines.get(index).cloned().unwrap_or("");
+            try!(writeln!(f, "{}{:>w$} |{} {}", self.style(BLUE), index + 1, self.style(RESET), line, w = width));
+            if index == error_line {
+                //The column depends on the tab width so the padding is taken from the source text
+                //between the start of the line and the error instead, keeping tabs so that the
+                //caret lines up with the source line. Positions which are not tied to an offset
+                //in the source, such as those from `SourcePosition::new`, use the column instead.
+                let padding: String = match self.source.get(..position.offset) {
+                    Some(before) if position.offset != 0 && before.matches('\n').count() == error_line => {
+                        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                        before[line_start..].chars()
+                            .filter(|&c| c != '\r')
+                            .map(|c| if c == '\t' { '\t' } else { ' ' })
+                            .collect()
+                    }
+                    _ => (1..position.column).map(|_| ' ').collect()
+                };
+                try!(writeln!(f, "{}{:w$} |{} {}{}^{}",
+                              self.style(BLUE), "", self.style(RESET),
+                              padding, self.style(RED), self.style(RESET), w = width));
+            }
+        }
+        self.error.write_messages(f)
+    }
+}
 impl fmt::Display for SourcePosition {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "line: {}, column: {}", self.line, self.column)
@@ -380,7 +577,7 @@ impl <I> fmt::Debug for State<I>
 
 impl <I: Stream> State<I> {
     pub fn new(input: I) -> State<I> {
-        State { position: <I::Item as Positioner>::start(), input: input }
+        State { position: input.start_position(), input: input }
     }
 
     pub fn as_empty(&self) -> State<I> {
@@ -392,22 +589,76 @@ impl <I: Stream> State<I> {
     ///according to the item that was extracted
     ///Usually you want to use `uncons_char` instead which works directly on character streams
     pub fn uncons(self) -> ParseResult<I::Item, I> {
-        let State { mut position, input, .. } = self;
-        match input.uncons() {
+        match self.input.clone().uncons() {
             Ok((c, input)) => {
-                c.update(&mut position);
+                let State { mut position, .. } = self;
+                input.update_position(&c, &mut position);
                 Ok((c, Consumed::Consumed(State { position: position, input: input })))
             }
-            Err(err) => Err(Consumed::Empty(ParseError::new(position, err)))
+            Err(err) => Err(self.uncons_error(err))
         }
     }
+
+    ///Returns the error to report when taking an item from `self` failed with `err`.
+    ///If `err` is the end of input of a partial stream more input is needed to continue so
+    ///`Consumed::Partial` is returned, otherwise the error is `Consumed::Empty`.
+    pub fn uncons_error(&self, err: Error<I::Item, I::Range>) -> Consumed<ParseError<I>> {
+        let partial = self.input.is_partial() && err == Error::end_of_input();
+        let error = ParseError::new(self.position.clone(), err);
+        if partial { Consumed::Partial(error) } else { Consumed::Empty(error) }
+    }
     pub fn update(mut self, i: I::Item, rest: I) -> ParseResult<I::Item, I> {
-        i.update(&mut self.position);
+        rest.update_position(&i, &mut self.position);
         self.input = rest;
         Ok((i, Consumed::Consumed(self)))
     }
 }
 
+impl <I> State<I>
+    where I: RangeStream {
+
+    ///Removes `size` items from the input returning them as a range.
+    ///Fails if there are fewer than `size` items left
+    pub fn uncons_range(self, size: usize) -> ParseResult<I::Range, I> {
+        match self.input.clone().uncons_range(size) {
+            Ok((range, input)) => {
+                let State { mut position, .. } = self;
+                input.update_range_position(&range, &mut position);
+                let state = State { position: position, input: input };
+                Ok((range, if size == 0 { Consumed::Empty(state) } else { Consumed::Consumed(state) }))
+            }
+            Err(err) => Err(self.uncons_error(err))
+        }
+    }
+
+    ///Removes items from the input while `predicate` returns `true`, returning them as a range
+    pub fn uncons_while<F>(self, mut predicate: F) -> ParseResult<I::Range, I>
+        where F: FnMut(I::Item) -> bool {
+        let State { mut position, input, .. } = self;
+        let start = input.clone();
+        let mut consumed = false;
+        let result = input.uncons_while(|c| {
+            let ok = predicate(c);
+            consumed = consumed || ok;
+            ok
+        });
+        match result {
+            Ok((range, input)) => {
+                input.update_range_position(&range, &mut position);
+                let state = State { position: position, input: input };
+                //If all of the input was taken then more input could have been taken as well
+                if state.input.is_partial() {
+                    if let Err(err) = state.input.clone().uncons() {
+                        return Err(state.uncons_error(err))
+                    }
+                }
+                Ok((range, if consumed { Consumed::Consumed(state) } else { Consumed::Empty(state) }))
+            }
+            Err(err) => Err(State { position: position, input: start }.uncons_error(err))
+        }
+    }
+}
+
 ///A type alias over the specific `Result` type used by parsers to indicate wether they were
 ///successful or not.
 ///`O` is the type that is output on success
@@ -419,10 +670,37 @@ pub type ParseResult<O, I> = Result<(O, Consumed<State<I>>), Consumed<ParseError
 ///A stream is a sequence of items that can be extracted one by one
 pub trait Stream : Clone {
     type Item: Positioner + Clone;
-    type Range: Positioner + Clone;
+    type Range: Positioner<Position=<Self::Item as Positioner>::Position> + Clone;
     ///Takes a stream and removes its first item, yielding the item and the rest of the elements
     ///Returns `Err` when no more elements could be retrieved
     fn uncons(self) -> Result<(Self::Item, Self), Error<Self::Item, Self::Range>>;
+
+    ///Updates `position` given that `item` has just been removed from the stream, leaving `self`.
+    ///The default implementation calls `Positioner::update` but streams may override it to
+    ///change how positions are computed.
+    fn update_position(&self, item: &Self::Item, position: &mut <Self::Item as Positioner>::Position) {
+        item.update(position)
+    }
+
+    ///Updates `position` given that `range` has just been removed from the stream, leaving `self`.
+    fn update_range_position(&self, range: &Self::Range, position: &mut <Self::Item as Positioner>::Position) {
+        range.update(position)
+    }
+
+    ///Returns true if the stream only contains the part of the input which has been received so
+    ///far, in which case reaching the end of the stream means that more input is needed rather
+    ///than that the input ended. Parsers fail with `Consumed::Partial` when they reach the end of
+    ///a partial stream.
+    fn is_partial(&self) -> bool {
+        false
+    }
+
+    ///Returns the position of the first item in the stream.
+    ///The default implementation returns `Positioner::start` but streams whose items carry their
+    ///own positions may override it.
+    fn start_position(&self) -> <Self::Item as Positioner>::Position {
+        <Self::Item as Positioner>::start()
+    }
 }
 
 impl <'a> Stream for &'a str {
@@ -450,6 +728,151 @@ impl <'a, T> Stream for &'a [T]
     }
 }
 
+///Trait representing a range of elements
+pub trait Range {
+    ///Returns the remaining length of `self`.
+    ///The returned length need not be the same as the number of items left in the stream
+    fn len(&self) -> usize;
+}
+
+impl <'a> Range for &'a str {
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+}
+
+impl <'a, T> Range for &'a [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+}
+
+///A `RangeStream` is an extension of `Stream` which allows for zero copy parsing by returning
+///ranges of the input directly instead of a collection of the parsed items
+pub trait RangeStream : Stream {
+    ///Takes `size` elements from the stream
+    ///Fails if the length of the stream is less than `size`.
+    fn uncons_range(self, size: usize) -> Result<(Self::Range, Self), Error<Self::Item, Self::Range>>;
+
+    ///Takes items from the stream, testing each one with `predicate`
+    ///returns the range of items which passed `predicate`
+    fn uncons_while<F>(self, predicate: F) -> Result<(Self::Range, Self), Error<Self::Item, Self::Range>>
+        where F: FnMut(Self::Item) -> bool;
+}
+
+impl <'a> RangeStream for &'a str {
+    fn uncons_range(self, size: usize) -> Result<(&'a str, &'a str), Error<char, &'a str>> {
+        if size <= self.len() {
+            if self.is_char_boundary(size) {
+                Ok((&self[..size], &self[size..]))
+            }
+            else {
+                Err(Error::Message("uncons_range on non character boundary".into()))
+            }
+        }
+        else {
+            Err(Error::end_of_input())
+        }
+    }
+    fn uncons_while<F>(self, mut predicate: F) -> Result<(&'a str, &'a str), Error<char, &'a str>>
+        where F: FnMut(char) -> bool {
+        let len = self.char_indices()
+            .find(|&(_, c)| !predicate(c))
+            .map(|(i, _)| i)
+            .unwrap_or(self.len());
+        Ok((&self[..len], &self[len..]))
+    }
+}
+
+impl <'a, T> RangeStream for &'a [T]
+    where T: Positioner {
+    fn uncons_range(self, size: usize) -> Result<(&'a [T], &'a [T]), Error<&'a T, &'a [T]>> {
+        if size <= self.len() {
+            Ok((&self[..size], &self[size..]))
+        }
+        else {
+            Err(Error::end_of_input())
+        }
+    }
+    fn uncons_while<F>(self, mut predicate: F) -> Result<(&'a [T], &'a [T]), Error<&'a T, &'a [T]>>
+        where F: FnMut(&'a T) -> bool {
+        let len = self.iter()
+            .position(|c| !predicate(c))
+            .unwrap_or(self.len());
+        Ok((&self[..len], &self[len..]))
+    }
+}
+
+///Wrapper around a slice which yields its items by value instead of by reference, letting parsers
+///which expect a stream of `T`, such as the parsers for `u8` in the `byte` module, parse a `&[T]`.
+///Returned by `from_slice`.
+#[derive(Debug, PartialEq)]
+pub struct SliceStream<'a, T: 'a>(&'a [T]);
+
+impl <'a, T> Clone for SliceStream<'a, T> {
+    fn clone(&self) -> SliceStream<'a, T> {
+        *self
+    }
+}
+
+impl <'a, T> Copy for SliceStream<'a, T> { }
+
+impl <'a, T> SliceStream<'a, T> {
+    ///Returns the items which are left in the stream
+    pub fn into_inner(self) -> &'a [T] {
+        self.0
+    }
+}
+
+///Converts a slice into a stream which yields its items by value.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::byte::bytes;
+/// # use pc::primitives::from_slice;
+/// # fn main() {
+/// let result = bytes(b"GET").parse(from_slice(&b"GET /"[..]))
+///     .map(|(value, rest)| (value, rest.into_inner()));
+/// assert_eq!(result, Ok((&b"GET"[..], &b" /"[..])));
+/// # }
+/// ```
+pub fn from_slice<'a, T>(slice: &'a [T]) -> SliceStream<'a, T>
+    where T: Positioner + Clone {
+    SliceStream(slice)
+}
+
+impl <'a, T> Stream for SliceStream<'a, T>
+    where T: Positioner + Clone {
+    type Item = T;
+    type Range = &'a [T];
+    fn uncons(self) -> Result<(T, SliceStream<'a, T>), Error<T, &'a [T]>> {
+        match self.0.split_first() {
+            Some((first, rest)) => Ok((first.clone(), SliceStream(rest))),
+            None => Err(Error::end_of_input())
+        }
+    }
+}
+
+impl <'a, T> RangeStream for SliceStream<'a, T>
+    where T: Positioner + Clone {
+    fn uncons_range(self, size: usize) -> Result<(&'a [T], SliceStream<'a, T>), Error<T, &'a [T]>> {
+        if size <= self.0.len() {
+            Ok((&self.0[..size], SliceStream(&self.0[size..])))
+        }
+        else {
+            Err(Error::end_of_input())
+        }
+    }
+    fn uncons_while<F>(self, mut predicate: F) -> Result<(&'a [T], SliceStream<'a, T>), Error<T, &'a [T]>>
+        where F: FnMut(T) -> bool {
+        let len = self.0.iter()
+            .position(|c| !predicate(c.clone()))
+            .unwrap_or(self.0.len());
+        Ok((&self.0[..len], SliceStream(&self.0[len..])))
+    }
+}
+
 ///Wrapper around iterators which allows them to be treated as a stream.
 ///Returned by `from_iter`.
 #[derive(Clone, Debug)]
@@ -475,6 +898,576 @@ impl <I: Iterator + Clone> Stream for IteratorStream<I>
     }
 }
 
+struct SharedBufferedStream<R> {
+    reader: R,
+    //The most recently read bytes, the last byte in the buffer is at `offset - 1`
+    buffer: VecDeque<u8>,
+    //The offset of the next byte which will be read from `reader`
+    offset: usize,
+    lookbehind: usize
+}
+
+impl <R: Read> SharedBufferedStream<R> {
+    fn read_byte(&mut self) -> Result<u8, Error<u8, u8>> {
+        let mut byte = [0];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return Err(Error::end_of_input()),
+                Ok(_) => break,
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err.into())
+            }
+        }
+        self.buffer.push_back(byte[0]);
+        while self.buffer.len() > self.lookbehind {
+            self.buffer.pop_front();
+        }
+        self.offset += 1;
+        Ok(byte[0])
+    }
+}
+
+///Stream which reads bytes from an `io::Read` instance.
+///The last `lookbehind` bytes are kept in a buffer which is shared between all clones of the
+///stream which lets parsers such as `try` and `or` backtrack as long as they do not need to
+///rewind further than `lookbehind` bytes.
+///Returned by `from_read`.
+pub struct BufferedStream<R> {
+    offset: usize,
+    shared: Rc<RefCell<SharedBufferedStream<R>>>
+}
+
+impl <R> Clone for BufferedStream<R> {
+    fn clone(&self) -> BufferedStream<R> {
+        BufferedStream { offset: self.offset, shared: self.shared.clone() }
+    }
+}
+
+impl <R> fmt::Debug for BufferedStream<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BufferedStream {{ offset: {:?} }}", self.offset)
+    }
+}
+
+///Converts an `io::Read` instance into a stream of bytes which can backtrack at most `lookbehind`
+///bytes. Since bytes are read one at a time the reader should usually be wrapped in an
+///`io::BufReader` if it is not buffered already.
+///Any I/O error is returned as an `Error::Other` from the stream.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::primitives::from_read;
+/// # fn main() {
+/// use std::io::Cursor;
+/// let input = from_read(Cursor::new(&b"abc"[..]), 3);
+/// let mut parser = try((token(b'a'), token(b'c')))
+///     .or((token(b'a'), token(b'b')));
+/// assert_eq!(parser.parse(input).map(|x| x.0), Ok((b'a', b'b')));
+/// # }
+/// ```
+pub fn from_read<R>(read: R, lookbehind: usize) -> BufferedStream<R>
+    where R: Read {
+    let shared = SharedBufferedStream {
+        reader: read,
+        buffer: VecDeque::with_capacity(lookbehind),
+        offset: 0,
+        lookbehind: lookbehind
+    };
+    BufferedStream { offset: 0, shared: Rc::new(RefCell::new(shared)) }
+}
+
+impl <R: Read> Stream for BufferedStream<R> {
+    type Item = u8;
+    type Range = u8;
+    fn uncons(self) -> Result<(u8, BufferedStream<R>), Error<u8, u8>> {
+        let byte = {
+            let mut shared = self.shared.borrow_mut();
+            if self.offset < shared.offset {
+                let behind = shared.offset - self.offset;
+                if behind > shared.buffer.len() {
+                    return Err(Error::Message("Backtracked too far".into()))
+                }
+                shared.buffer[shared.buffer.len() - behind]
+            }
+            else {
+                try!(shared.read_byte())
+            }
+        };
+        Ok((byte, BufferedStream { offset: self.offset + 1, shared: self.shared }))
+    }
+}
+
+///Enum specifying which character sequences are treated as line breaks by a `SourceStream`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineEndings {
+    ///Only `\n` ends a line (`\r\n` is also handled as `\r` is just another character)
+    Lf,
+    ///`\n`, `\r\n` and a lone `\r` each end a line
+    Any
+}
+
+///Policy used by `SourceStream` to compute the `SourcePosition` of characters
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PositionPolicy {
+    ///Tab characters advance the column to the next multiple of `tab_width` (plus one).
+    ///A tab width of 1 counts a tab as a single column
+    pub tab_width: i32,
+    ///Which character sequences end a line
+    pub line_endings: LineEndings
+}
+
+impl Default for PositionPolicy {
+    ///The default policy, which is the same as `Positioner` for `char`
+    fn default() -> PositionPolicy {
+        PositionPolicy { tab_width: 1, line_endings: LineEndings::Lf }
+    }
+}
+
+impl PositionPolicy {
+    fn update(&self, c: char, next: Option<char>, position: &mut SourcePosition) {
+        let line_break = match (c, self.line_endings) {
+            ('\n', _) => true,
+            ('\r', LineEndings::Any) => next != Some('\n'),
+            _ => false
+        };
+        position.offset += c.len_utf8();
+        if line_break {
+            position.line += 1;
+            position.column = 1;
+            position.utf16_column = 1;
+            return
+        }
+        position.utf16_column += c.len_utf16() as i32;
+        if c == '\t' && self.tab_width > 1 {
+            position.column = ((position.column - 1) / self.tab_width + 1) * self.tab_width + 1;
+        }
+        else {
+            position.column += 1;
+        }
+    }
+}
+
+///Wrapper around a character stream which computes positions according to a `PositionPolicy`
+///instead of the fixed rules of `Positioner` for `char`.
+///
+///A lone `\r` is only known to be a line break once the character after it has been seen, if the
+///input ends in `\r` (for instance when parsing partial input) it is treated as a line break.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::primitives::{LineEndings, PositionPolicy, SourcePosition, SourceStream};
+/// # fn main() {
+/// let policy = PositionPolicy { tab_width: 8, line_endings: LineEndings::Any };
+/// let input = SourceStream::new("a\r\tb", policy);
+/// let result = many::<String, _>(satisfy(|c| c != 'b'))
+///     .parse_state(State::new(input))
+///     .map(|(_, rest)| rest.into_inner().position);
+/// assert_eq!(result, Ok(SourcePosition { line: 2, column: 9, offset: 3, utf16_column: 2 }));
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SourceStream<I> {
+    input: I,
+    policy: PositionPolicy
+}
+
+impl <I> SourceStream<I>
+    where I: Stream<Item=char> {
+    ///Wraps `input` so that positions are computed using `policy`
+    pub fn new(input: I, policy: PositionPolicy) -> SourceStream<I> {
+        SourceStream { input: input, policy: policy }
+    }
+
+    ///Returns the wrapped stream
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input.clone().uncons().ok().map(|(c, _)| c)
+    }
+}
+
+impl <I> Stream for SourceStream<I>
+    where I: Stream<Item=char>
+        , I::Range: AsRef<str> {
+    type Item = char;
+    type Range = I::Range;
+    fn uncons(self) -> Result<(char, SourceStream<I>), Error<char, I::Range>> {
+        let (c, input) = try!(self.input.uncons());
+        Ok((c, SourceStream { input: input, policy: self.policy }))
+    }
+    fn update_position(&self, item: &char, position: &mut SourcePosition) {
+        self.policy.update(*item, self.peek(), position)
+    }
+    fn update_range_position(&self, range: &I::Range, position: &mut SourcePosition) {
+        let mut chars = range.as_ref().chars().peekable();
+        while let Some(c) = chars.next() {
+            let next = match chars.peek() {
+                Some(&next) => Some(next),
+                None => self.peek()
+            };
+            self.policy.update(c, next, position);
+        }
+    }
+    fn is_partial(&self) -> bool {
+        self.input.is_partial()
+    }
+    fn start_position(&self) -> SourcePosition {
+        self.input.start_position()
+    }
+}
+
+impl <I> MemoStream for SourceStream<I>
+    where I: MemoStream<Item=char>
+        , I::Range: AsRef<str> {
+    type Key = I::Key;
+    fn memo_key(&self) -> I::Key {
+        self.input.memo_key()
+    }
+}
+
+impl <I> RangeStream for SourceStream<I>
+    where I: RangeStream<Item=char>
+        , I::Range: AsRef<str> {
+    fn uncons_range(self, size: usize) -> Result<(I::Range, SourceStream<I>), Error<char, I::Range>> {
+        let (range, input) = try!(self.input.uncons_range(size));
+        Ok((range, SourceStream { input: input, policy: self.policy }))
+    }
+    fn uncons_while<F>(self, predicate: F) -> Result<(I::Range, SourceStream<I>), Error<char, I::Range>>
+        where F: FnMut(char) -> bool {
+        let (range, input) = try!(self.input.uncons_while(predicate));
+        Ok((range, SourceStream { input: input, policy: self.policy }))
+    }
+}
+
+///Wrapper around a stream which carries a user defined state through the parse.
+///The state is a part of the stream so when a parser backtracks, for instance through `try` or
+///`or`, the state is restored to what it was at the position being backtracked to.
+///As the state is cloned along with the stream it should be cheap to clone.
+///
+///The state is accessed with the `get_state`, `put_state` and `modify_state` parsers in the
+///`combinator` module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatefulStream<I, U> {
+    input: I,
+    state: U
+}
+
+impl <I, U> StatefulStream<I, U>
+    where I: Stream
+        , U: Clone {
+    ///Wraps `input` with the initial user state `state`
+    pub fn new(input: I, state: U) -> StatefulStream<I, U> {
+        StatefulStream { input: input, state: state }
+    }
+
+    ///Returns a reference to the user state
+    pub fn state(&self) -> &U {
+        &self.state
+    }
+
+    ///Returns a mutable reference to the user state
+    pub fn state_mut(&mut self) -> &mut U {
+        &mut self.state
+    }
+
+    ///Returns the wrapped stream and the user state
+    pub fn into_parts(self) -> (I, U) {
+        (self.input, self.state)
+    }
+}
+
+impl <I, U> Stream for StatefulStream<I, U>
+    where I: Stream
+        , U: Clone {
+    type Item = I::Item;
+    type Range = I::Range;
+    fn uncons(self) -> Result<(I::Item, StatefulStream<I, U>), Error<I::Item, I::Range>> {
+        let StatefulStream { input, state } = self;
+        let (c, input) = try!(input.uncons());
+        Ok((c, StatefulStream { input: input, state: state }))
+    }
+    fn update_position(&self, item: &I::Item, position: &mut <I::Item as Positioner>::Position) {
+        self.input.update_position(item, position)
+    }
+    fn update_range_position(&self, range: &I::Range, position: &mut <I::Item as Positioner>::Position) {
+        self.input.update_range_position(range, position)
+    }
+    fn is_partial(&self) -> bool {
+        self.input.is_partial()
+    }
+    fn start_position(&self) -> <I::Item as Positioner>::Position {
+        self.input.start_position()
+    }
+}
+
+impl <I, U> MemoStream for StatefulStream<I, U>
+    where I: MemoStream
+        , U: Clone + Ord {
+    type Key = (I::Key, U);
+    fn memo_key(&self) -> (I::Key, U) {
+        (self.input.memo_key(), self.state.clone())
+    }
+}
+
+impl <I, U> RangeStream for StatefulStream<I, U>
+    where I: RangeStream
+        , U: Clone {
+    fn uncons_range(self, size: usize) -> Result<(I::Range, StatefulStream<I, U>), Error<I::Item, I::Range>> {
+        let StatefulStream { input, state } = self;
+        let (range, input) = try!(input.uncons_range(size));
+        Ok((range, StatefulStream { input: input, state: state }))
+    }
+    fn uncons_while<F>(self, predicate: F) -> Result<(I::Range, StatefulStream<I, U>), Error<I::Item, I::Range>>
+        where F: FnMut(I::Item) -> bool {
+        let StatefulStream { input, state } = self;
+        let (range, input) = try!(input.uncons_while(predicate));
+        Ok((range, StatefulStream { input: input, state: state }))
+    }
+}
+
+struct RecoveredError<I>
+    where I: Stream {
+    error: ParseError<I>,
+    next: Option<Rc<RecoveredError<I>>>
+}
+
+///Wrapper around a stream which carries the errors that `recover_with` has recovered from.
+///The errors are part of the stream so when a parser such as `try` or `or` backtracks, the errors
+///which were recovered from on the abandoned path are discarded along with it. Cloning the stream
+///is cheap as the errors are shared between the clones.
+pub struct RecoverStream<I>
+    where I: Stream {
+    input: I,
+    errors: Option<Rc<RecoveredError<I>>>
+}
+
+impl <I> Clone for RecoverStream<I>
+    where I: Stream {
+    fn clone(&self) -> RecoverStream<I> {
+        RecoverStream { input: self.input.clone(), errors: self.errors.clone() }
+    }
+}
+
+impl <I> fmt::Debug for RecoverStream<I>
+    where I: Stream + fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RecoverStream {{ input: {:?}, errors: {} }}", self.input, self.errors().len())
+    }
+}
+
+impl <I> RecoverStream<I>
+    where I: Stream {
+    ///Wraps `input` to allow errors to be recovered from with `recover_with`
+    pub fn new(input: I) -> RecoverStream<I> {
+        RecoverStream { input: input, errors: None }
+    }
+
+    ///Records `error` as having been recovered from
+    pub fn push(&mut self, error: ParseError<I>) {
+        let next = self.errors.take();
+        self.errors = Some(Rc::new(RecoveredError { error: error, next: next }));
+    }
+
+    ///Returns the errors which have been recovered from before reaching this stream, sorted by
+    ///position
+    pub fn errors(&self) -> Vec<&ParseError<I>> {
+        let mut errors = Vec::new();
+        let mut node = self.errors.as_ref();
+        while let Some(recovered) = node {
+            errors.push(&recovered.error);
+            node = recovered.next.as_ref();
+        }
+        errors.reverse();
+        errors.sort_by(|l, r| l.position.cmp(&r.position));
+        errors
+    }
+
+    ///Returns the wrapped stream
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+}
+
+impl <I> Stream for RecoverStream<I>
+    where I: Stream {
+    type Item = I::Item;
+    type Range = I::Range;
+    fn uncons(self) -> Result<(I::Item, RecoverStream<I>), Error<I::Item, I::Range>> {
+        let RecoverStream { input, errors } = self;
+        let (c, input) = try!(input.uncons());
+        Ok((c, RecoverStream { input: input, errors: errors }))
+    }
+    fn update_position(&self, item: &I::Item, position: &mut <I::Item as Positioner>::Position) {
+        self.input.update_position(item, position)
+    }
+    fn update_range_position(&self, range: &I::Range, position: &mut <I::Item as Positioner>::Position) {
+        self.input.update_range_position(range, position)
+    }
+    fn is_partial(&self) -> bool {
+        self.input.is_partial()
+    }
+    fn start_position(&self) -> <I::Item as Positioner>::Position {
+        self.input.start_position()
+    }
+}
+
+impl <I> RangeStream for RecoverStream<I>
+    where I: RangeStream {
+    fn uncons_range(self, size: usize) -> Result<(I::Range, RecoverStream<I>), Error<I::Item, I::Range>> {
+        let RecoverStream { input, errors } = self;
+        let (range, input) = try!(input.uncons_range(size));
+        Ok((range, RecoverStream { input: input, errors: errors }))
+    }
+    fn uncons_while<F>(self, predicate: F) -> Result<(I::Range, RecoverStream<I>), Error<I::Item, I::Range>>
+        where F: FnMut(I::Item) -> bool {
+        let RecoverStream { input, errors } = self;
+        let (range, input) = try!(input.uncons_while(predicate));
+        Ok((range, RecoverStream { input: input, errors: errors }))
+    }
+}
+
+struct PartialBuffer<T> {
+    //The items which have been received but not released yet, the first item is at `start`
+    items: VecDeque<T>,
+    start: usize,
+    //Set once it is known that no more items will be received
+    complete: bool
+}
+
+///Stream over input which is received in chunks, for instance frames read from a socket.
+///The received items are kept in a buffer which is shared between all clones of the stream so a
+///parse which was suspended at the end of the buffer sees the items which are received later.
+///The stream is partial (see `Stream::is_partial`) until `PartialParser::finish` is called.
+///Created and fed by `PartialParser`.
+pub struct PartialStream<T> {
+    offset: usize,
+    buffer: Rc<RefCell<PartialBuffer<T>>>
+}
+
+impl <T> Clone for PartialStream<T> {
+    fn clone(&self) -> PartialStream<T> {
+        PartialStream { offset: self.offset, buffer: self.buffer.clone() }
+    }
+}
+
+impl <T> fmt::Debug for PartialStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PartialStream {{ offset: {:?} }}", self.offset)
+    }
+}
+
+impl <T> PartialStream<T> {
+    //Drops the received items before `self` as no parser will backtrack to them
+    fn release(&self) {
+        let mut buffer = self.buffer.borrow_mut();
+        let released = self.offset - buffer.start;
+        buffer.items.drain(..released);
+        buffer.start = self.offset;
+    }
+}
+
+impl <T> Stream for PartialStream<T>
+    where T: Positioner + Clone {
+    type Item = T;
+    type Range = T;
+    fn uncons(self) -> Result<(T, PartialStream<T>), Error<T, T>> {
+        let item = {
+            let buffer = self.buffer.borrow();
+            if self.offset < buffer.start {
+                return Err(Error::Message("Backtracked too far".into()))
+            }
+            match buffer.items.get(self.offset - buffer.start) {
+                Some(item) => item.clone(),
+                None => return Err(Error::end_of_input())
+            }
+        };
+        Ok((item, PartialStream { offset: self.offset + 1, buffer: self.buffer }))
+    }
+    fn is_partial(&self) -> bool {
+        !self.buffer.borrow().complete
+    }
+}
+
+///Runs a `Resumable` parser on input which is received in chunks.
+///When the parser reaches the end of the input received so far the parse is suspended and
+///`parse` returns `Ok(None)`. Once more input has been added with `extend` the next call to
+///`parse` resumes the suspended parse, without parsing the earlier chunks again.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::primitives::PartialParser;
+/// # fn main() {
+/// let mut parser = PartialParser::new(many1::<String, _>(digit()).skip(char(';')));
+/// let mut frames = Vec::new();
+/// for chunk in &["12;3", "4;", "56;7"] {
+///     parser.extend(chunk.chars());
+///     while let Some(frame) = parser.parse().unwrap() {
+///         frames.push(frame);
+///     }
+/// }
+/// assert_eq!(frames, ["12", "34", "56"]);
+/// //No more input will arrive so the last frame is incomplete
+/// parser.finish();
+/// assert!(parser.parse().is_err());
+/// # }
+/// ```
+pub struct PartialParser<P, T>
+    where P: Resumable<Input=PartialStream<T>>
+        , T: Positioner + Clone {
+    parser: P,
+    state: P::PartialState,
+    input: State<PartialStream<T>>
+}
+
+impl <P, T> PartialParser<P, T>
+    where P: Resumable<Input=PartialStream<T>>
+        , T: Positioner + Clone {
+    ///Creates a driver for `parser` which has not received any input yet
+    pub fn new(parser: P) -> PartialParser<P, T> {
+        let buffer = PartialBuffer { items: VecDeque::new(), start: 0, complete: false };
+        let input = PartialStream { offset: 0, buffer: Rc::new(RefCell::new(buffer)) };
+        PartialParser { parser: parser, state: Default::default(), input: State::new(input) }
+    }
+
+    ///Adds `items` to the end of the input
+    pub fn extend<I>(&mut self, items: I)
+        where I: IntoIterator<Item=T> {
+        self.input.input.buffer.borrow_mut().items.extend(items)
+    }
+
+    ///Marks that no more input will be added, after which reaching the end of the input is an
+    ///error as it is for any other stream
+    pub fn finish(&mut self) {
+        self.input.input.buffer.borrow_mut().complete = true;
+    }
+
+    ///Returns the number of received items which have not been consumed by a successful parse
+    pub fn buffered(&self) -> usize {
+        let buffer = self.input.input.buffer.borrow();
+        buffer.items.len() - (self.input.input.offset - buffer.start)
+    }
+
+    ///Parses a value from the input, resuming the previous parse if it was suspended.
+    ///Returns `Ok(None)` if more input is needed before the value can be parsed. On success the
+    ///input used by the value is released and the next call starts parsing a new value after it.
+    pub fn parse(&mut self) -> Result<Option<P::Output>, ParseError<PartialStream<T>>> {
+        match self.parser.parse_resume_state(self.input.clone(), &mut self.state) {
+            Ok((value, rest)) => {
+                self.input = rest.into_inner();
+                self.input.input.release();
+                Ok(Some(value))
+            }
+            Err(Consumed::Partial(_)) => Ok(None),
+            Err(err) => Err(err.into_inner())
+        }
+    }
+}
+
 ///`Positioner` represents the operations needed to update a position given an item from the stream
 ///When implementing stream for custom token type this must be implemented for that token to allow
 ///the position to be updated
@@ -522,17 +1515,32 @@ impl Positioner for str {
 impl Positioner for char {
     type Position = SourcePosition;
     fn start() -> SourcePosition {
-        SourcePosition { line: 1, column: 1 }
+        SourcePosition::new(1, 1)
     }
     fn update(&self, position: &mut SourcePosition) {
         position.column += 1;
+        position.offset += self.len_utf8();
+        position.utf16_column += self.len_utf16() as i32;
         if *self == '\n' {
             position.column = 1;
+            position.utf16_column = 1;
             position.line += 1;
         }
     }
 }
 
+impl Positioner for IndexPosition {
+    type Position = IndexPosition;
+
+    fn start() -> IndexPosition {
+        IndexPosition { index: 0 }
+    }
+
+    fn update(&self, position: &mut IndexPosition) {
+        position.index += 1;
+    }
+}
+
 impl Positioner for u8 {
     type Position = BytePosition;
 
@@ -589,6 +1597,12 @@ pub trait Parser {
     ///Adds the first error that would normally be returned by this parser if it failed
     fn add_error(&mut self, _error: &mut ParseError<Self::Input>) {
     }
+
+    ///Adds the errors of the attempts which failed without consuming input at the position where
+    ///the last successful parse stopped, such as the operators tried after the last operand of an
+    ///expression. Called by sequencing parsers when the next parser fails at that same position.
+    fn add_trailing_error(&mut self, _error: &mut ParseError<Self::Input>) {
+    }
 }
 impl <'a, I, O, P: ?Sized> Parser for &'a mut P 
     where I: Stream, P: Parser<Input=I, Output=O> {
@@ -603,6 +1617,9 @@ impl <'a, I, O, P: ?Sized> Parser for &'a mut P
     fn add_error(&mut self, error: &mut ParseError<Self::Input>) {
         (**self).add_error(error)
     }
+    fn add_trailing_error(&mut self, error: &mut ParseError<Self::Input>) {
+        (**self).add_trailing_error(error)
+    }
 }
 impl <I, O, P: ?Sized> Parser for Box<P> 
     where I: Stream, P: Parser<Input=I, Output=O> {
@@ -617,4 +1634,56 @@ impl <I, O, P: ?Sized> Parser for Box<P>
     fn add_error(&mut self, error: &mut ParseError<Self::Input>) {
         (**self).add_error(error)
     }
+    fn add_trailing_error(&mut self, error: &mut ParseError<Self::Input>) {
+        (**self).add_trailing_error(error)
+    }
+}
+
+///A parser which can be suspended when it reaches the end of a partial stream (see
+///`Stream::is_partial`) and resumed once more input has been received.
+///
+///The progress made before the parse was suspended is stored in `PartialState` so that the input
+///which has already been parsed does not need to be parsed again. Parsers which do not store any
+///progress use `()` as their state and parse their input again from the start when resumed.
+pub trait Resumable: Parser {
+    ///The progress of a suspended parse, `Default::default()` being a parse which has not started
+    type PartialState: Default;
+
+    ///Like `parse_lazy` but resumes the parse stored in `state`.
+    ///If the parser fails with `Consumed::Partial` its progress is stored in `state` and calling
+    ///`parse_resume` again with the same `input` continues from where the parse was suspended.
+    ///On success or any other error `state` is left as `Default::default()`.
+    fn parse_resume(&mut self, input: State<Self::Input>, state: &mut Self::PartialState) -> ParseResult<Self::Output, Self::Input>;
+
+    ///Like `parse_state` but resumes the parse stored in `state`, see `parse_resume`
+    fn parse_resume_state(&mut self, input: State<Self::Input>, state: &mut Self::PartialState) -> ParseResult<Self::Output, Self::Input> {
+        let mut result = self.parse_resume(input.clone(), state);
+        if let Err(Consumed::Empty(ref mut error)) = result {
+            if let Ok((t, _)) = input.input.uncons() {
+                error.add_error(Error::Unexpected(Info::Token(t)));
+            }
+            self.add_error(error);
+        }
+        result
+    }
+}
+impl <'a, P: ?Sized> Resumable for &'a mut P
+    where P: Resumable {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<P::Input>, state: &mut P::PartialState) -> ParseResult<P::Output, P::Input> {
+        (**self).parse_resume(input, state)
+    }
+    fn parse_resume_state(&mut self, input: State<P::Input>, state: &mut P::PartialState) -> ParseResult<P::Output, P::Input> {
+        (**self).parse_resume_state(input, state)
+    }
+}
+impl <P: ?Sized> Resumable for Box<P>
+    where P: Resumable {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<P::Input>, state: &mut P::PartialState) -> ParseResult<P::Output, P::Input> {
+        (**self).parse_resume(input, state)
+    }
+    fn parse_resume_state(&mut self, input: State<P::Input>, state: &mut P::PartialState) -> ParseResult<P::Output, P::Input> {
+        (**self).parse_resume_state(input, state)
+    }
 }
@@ -0,0 +1,429 @@
+use std::marker::PhantomData;
+use primitives::{Consumed, Error, Info, Parser, ParseError, ParseResult, RangeStream, State};
+use primitives::Range as StreamRange;
+
+#[derive(Clone)]
+pub struct Range<I>(I::Range)
+    where I: RangeStream;
+
+impl <I> Parser for Range<I>
+    where I: RangeStream
+        , I::Range: StreamRange {
+
+    type Input = I;
+    type Output = I::Range;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<I::Range, I> {
+        let position = input.position.clone();
+        match input.uncons_range(self.0.len()) {
+            Ok((other, rest)) => {
+                if other == self.0 {
+                    Ok((other, rest))
+                }
+                else {
+                    Err(Consumed::Empty(ParseError::empty(position)))
+                }
+            }
+            Err(err) => Err(err)
+        }
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        errors.add_error(Error::Expected(Info::Range(self.0.clone())));
+    }
+}
+
+///Zero-copy parser which reads a range of length `i.len()` and succeds if `i` is equal to that range.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::range::range;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = range("hello");
+/// let result = parser.parse("hello world");
+/// assert_eq!(result, Ok(("hello", " world")));
+/// let result = parser.parse("hel world");
+/// assert!(result.is_err());
+/// # }
+/// ```
+pub fn range<I>(i: I::Range) -> Range<I>
+    where I: RangeStream
+        , I::Range: StreamRange {
+    Range(i)
+}
+
+#[derive(Clone)]
+pub struct Take<I>(usize, PhantomData<fn (I) -> I>);
+impl <I> Parser for Take<I>
+    where I: RangeStream {
+
+    type Input = I;
+    type Output = I::Range;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<I::Range, I> {
+        input.uncons_range(self.0)
+    }
+}
+
+///Zero-copy parser which reads a range of length `n`.
+///For `&str` streams the length is counted in bytes and the parser fails if `n` does not lie on
+///a character boundary.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::range::take;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = take(1);
+/// let result = parser.parse("1");
+/// assert_eq!(result, Ok(("1", "")));
+/// let mut parser = take(4);
+/// let result = parser.parse("123abc");
+/// assert_eq!(result, Ok(("123a", "bc")));
+/// let result = parser.parse("abc");
+/// assert!(result.is_err());
+/// # }
+/// ```
+pub fn take<I>(n: usize) -> Take<I>
+    where I: RangeStream {
+    Take(n, PhantomData)
+}
+
+#[derive(Clone)]
+pub struct TakeWhile<I, F>(F, PhantomData<fn (I) -> I>);
+impl <I, F> Parser for TakeWhile<I, F>
+    where I: RangeStream
+        , F: FnMut(I::Item) -> bool {
+
+    type Input = I;
+    type Output = I::Range;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<I::Range, I> {
+        input.uncons_while(&mut self.0)
+    }
+}
+
+///Zero-copy parser which reads a range of 0 or more tokens which satisfy `f`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::range::take_while;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = take_while(|c: char| c.is_digit(10));
+/// let result = parser.parse("123abc");
+/// assert_eq!(result, Ok(("123", "abc")));
+/// let result = parser.parse("abc");
+/// assert_eq!(result, Ok(("", "abc")));
+/// # }
+/// ```
+pub fn take_while<I, F>(f: F) -> TakeWhile<I, F>
+    where I: RangeStream
+        , F: FnMut(I::Item) -> bool {
+    TakeWhile(f, PhantomData)
+}
+
+#[derive(Clone)]
+pub struct TakeWhile1<I, F>(F, PhantomData<fn (I) -> I>);
+impl <I, F> Parser for TakeWhile1<I, F>
+    where I: RangeStream
+        , F: FnMut(I::Item) -> bool {
+
+    type Input = I;
+    type Output = I::Range;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<I::Range, I> {
+        let position = input.position.clone();
+        match input.uncons_while(&mut self.0) {
+            Ok((_, Consumed::Empty(_))) => Err(Consumed::Empty(ParseError::empty(position))),
+            result => result
+        }
+    }
+}
+
+///Zero-copy parser which reads a range of 1 or more tokens which satisfy `f`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::range::take_while1;
+/// # use pc::*;
+/// # fn main() {
+/// let mut parser = take_while1(|c: char| c.is_digit(10));
+/// let result = parser.parse("123abc");
+/// assert_eq!(result, Ok(("123", "abc")));
+/// let result = parser.parse("abc");
+/// assert!(result.is_err());
+/// # }
+/// ```
+pub fn take_while1<I, F>(f: F) -> TakeWhile1<I, F>
+    where I: RangeStream
+        , F: FnMut(I::Item) -> bool {
+    TakeWhile1(f, PhantomData)
+}
+
+impl_restart!(Range<I> [I: RangeStream], Take<I>, TakeWhile<I, F>, TakeWhile1<I, F>);
+
+#[cfg(feature = "regex")]
+pub use self::regex::{regex, regex_captures, Regex, RegexCaptures, RegexError, RegexInput};
+
+#[cfg(feature = "regex")]
+mod regex {
+    use std::marker::PhantomData;
+    use regex_automata::{Anchored, Input};
+    use regex_automata::meta;
+    use regex_automata::util::captures::Captures;
+    use regex_automata::util::syntax;
+    use primitives::{Consumed, Error, Info, Parser, ParseError, ParseResult, RangeStream, State};
+
+    ///Error returned by `regex` and `regex_captures` when the pattern is not a valid regex
+    pub type RegexError = meta::BuildError;
+
+    ///Trait implemented by the inputs which can be matched by `regex` and `regex_captures`, `&str`
+    ///and `&[u8]`
+    pub trait RegexInput: RangeStream<Range=Self> {
+        ///Compiles `pattern` into a regex which matches this type of input
+        fn compile(pattern: &str) -> Result<meta::Regex, RegexError>;
+        ///Returns the bytes of the input
+        fn as_haystack(&self) -> &[u8];
+        ///Returns the part of the input between the byte offsets `start` and `end`
+        fn slice(&self, start: usize, end: usize) -> Self;
+    }
+
+    impl <'a> RegexInput for &'a str {
+        fn compile(pattern: &str) -> Result<meta::Regex, RegexError> {
+            meta::Regex::new(pattern)
+        }
+        fn as_haystack(&self) -> &[u8] {
+            self.as_bytes()
+        }
+        fn slice(&self, start: usize, end: usize) -> &'a str {
+            &self[start..end]
+        }
+    }
+
+    impl <'a> RegexInput for &'a [u8] {
+        fn compile(pattern: &str) -> Result<meta::Regex, RegexError> {
+            //Like `regex::bytes::Regex` the regex may match bytes which are not valid UTF-8
+            meta::Builder::new()
+                .configure(meta::Config::new().utf8_empty(false))
+                .syntax(syntax::Config::new().utf8(false))
+                .build(pattern)
+        }
+        fn as_haystack(&self) -> &[u8] {
+            self
+        }
+        fn slice(&self, start: usize, end: usize) -> &'a [u8] {
+            &self[start..end]
+        }
+    }
+
+    //Input which only matches at the start of `haystack` so that a failed match does not search
+    //the rest of the input
+    fn anchored<'a>(haystack: &'a [u8]) -> Input<'a> {
+        Input::new(haystack).anchored(Anchored::Yes)
+    }
+
+    #[derive(Clone)]
+    pub struct Regex<I> {
+        regex: meta::Regex,
+        pattern: String,
+        _marker: PhantomData<fn (I) -> I>
+    }
+
+    impl <I> Parser for Regex<I>
+        where I: RegexInput {
+        type Input = I;
+        type Output = I;
+        fn parse_lazy(&mut self, input: State<I>) -> ParseResult<I, I> {
+            let end = self.regex.search(&anchored(input.input.as_haystack())).map(|m| m.end());
+            match end {
+                Some(end) => input.uncons_range(end),
+                None => Err(Consumed::Empty(ParseError::empty(input.position)))
+            }
+        }
+        fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+            errors.add_error(Error::Expected(Info::Owned(self.pattern.clone())));
+        }
+    }
+
+    ///Zero-copy parser which matches the regex `pattern` at the start of the input and returns the
+    ///matched range. Works on both `&str` and `&[u8]`, and the pattern is only ever matched at the
+    ///current position so a failed match does not look at the rest of the input. On failure the
+    ///pattern is reported as the expected item.
+    ///Returns an error if `pattern` is not a valid regex. Flags such as case insensitivity are
+    ///set inside the pattern, for instance `(?i)select`.
+    ///
+    ///Requires the `regex` feature.
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::range::regex;
+    /// # fn main() {
+    /// let mut float = regex(r"[0-9]+\.[0-9]*").unwrap();
+    /// assert_eq!(float.parse("12.5 + 1"), Ok(("12.5", " + 1")));
+    /// //Only matches at the start of the input
+    /// assert!(float.parse("a 12.5").is_err());
+    /// # }
+    /// ```
+    pub fn regex<I>(pattern: &str) -> Result<Regex<I>, RegexError>
+        where I: RegexInput {
+        let regex = try!(I::compile(pattern));
+        Ok(Regex { regex: regex, pattern: pattern.to_string(), _marker: PhantomData })
+    }
+
+    #[derive(Clone)]
+    pub struct RegexCaptures<I> {
+        regex: meta::Regex,
+        captures: Captures,
+        pattern: String,
+        _marker: PhantomData<fn (I) -> I>
+    }
+
+    impl <I> Parser for RegexCaptures<I>
+        where I: RegexInput {
+        type Input = I;
+        type Output = Vec<Option<I>>;
+        fn parse_lazy(&mut self, input: State<I>) -> ParseResult<Vec<Option<I>>, I> {
+            self.regex.search_captures(&anchored(input.input.as_haystack()), &mut self.captures);
+            match self.captures.get_match() {
+                Some(m) => {
+                    let captures = (0..self.captures.group_len())
+                        .map(|group| {
+                            self.captures.get_group(group).map(|span| input.input.slice(span.start, span.end))
+                        })
+                        .collect();
+                    input.uncons_range(m.end())
+                        .map(|(_, rest)| (captures, rest))
+                }
+                None => Err(Consumed::Empty(ParseError::empty(input.position)))
+            }
+        }
+        fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+            errors.add_error(Error::Expected(Info::Owned(self.pattern.clone())));
+        }
+    }
+
+    ///Zero-copy parser which matches the regex `pattern` at the start of the input and returns
+    ///all capture groups of the match, the first group being the entire match.
+    ///Returns an error if `pattern` is not a valid regex.
+    ///
+    ///Requires the `regex` feature.
+    ///
+    /// ```
+    /// # extern crate combine as pc;
+    /// # use pc::*;
+    /// # use pc::range::regex_captures;
+    /// # fn main() {
+    /// let mut pair = regex_captures(r"(\w+)=(\w+)?").unwrap();
+    /// assert_eq!(pair.parse(&b"key=value;"[..]).map(|x| x.0),
+    ///            Ok(vec![Some(&b"key=value"[..]), Some(&b"key"[..]), Some(&b"value"[..])]));
+    /// assert_eq!(pair.parse(&b"key="[..]).map(|x| x.0),
+    ///            Ok(vec![Some(&b"key="[..]), Some(&b"key"[..]), None]));
+    /// # }
+    /// ```
+    pub fn regex_captures<I>(pattern: &str) -> Result<RegexCaptures<I>, RegexError>
+        where I: RegexInput {
+        let regex = try!(I::compile(pattern));
+        Ok(RegexCaptures {
+            captures: regex.create_captures(),
+            regex: regex,
+            pattern: pattern.to_string(),
+            _marker: PhantomData
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use primitives::{Error, Parser, SourcePosition, State};
+    use combinator::ParserExt;
+
+    #[test]
+    fn take_while_test() {
+        let result = take_while(|c: char| c.is_digit(10)).parse("123abc");
+        assert_eq!(result, Ok(("123", "abc")));
+        let result = take_while(|c: char| c.is_digit(10)).parse("abc");
+        assert_eq!(result, Ok(("", "abc")));
+    }
+
+    #[test]
+    fn take_while1_test() {
+        let result = take_while1(|c: char| c.is_digit(10)).parse("123abc");
+        assert_eq!(result, Ok(("123", "abc")));
+        let result = take_while1(|c: char| c.is_digit(10)).parse("abc");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn range_string_no_char_boundary_error() {
+        let mut parser = range("hello");
+        let result = parser.parse("hell\u{00EE} world");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn range_error() {
+        let result = range("abc").parse("abd");
+        assert_eq!(result.map_err(|err| err.errors), Err(vec![
+            Error::Unexpected('a'.into()),
+            Error::Expected(Info::Range("abc"))
+        ]));
+    }
+
+    #[test]
+    fn range_position() {
+        let result = take_while(|c: char| c.is_alphabetic())
+            .skip(range("\n"))
+            .and(take(3))
+            .parse_state(State::new("abc\ndef"))
+            .map(|(v, rest)| (v, rest.into_inner().position));
+        assert_eq!(result, Ok((("abc", "def"), SourcePosition { line: 2, column: 4, offset: 7, utf16_column: 4 })));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_position() {
+        let result = (regex(r"\w+\s+").unwrap(), regex(r"\d+").unwrap())
+            .parse_state(State::new("ab\u{e5}  \n 12!"))
+            .map(|(v, rest)| (v, rest.into_inner().position));
+        assert_eq!(result, Ok((("ab\u{e5}  \n ", "12"),
+                               SourcePosition { line: 2, column: 4, offset: 10, utf16_column: 4 })));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_error() {
+        let result = regex(r"\d+|x").unwrap().parse("a1");
+        assert_eq!(result.map_err(|err| err.errors), Err(vec![
+            Error::Unexpected('a'.into()),
+            Error::Expected(r"\d+|x".to_string().into())
+        ]));
+        assert!(regex::<&str>("(").is_err());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_inline_flags() {
+        let mut keyword = regex("(?i)select").unwrap();
+        assert_eq!(keyword.parse("SELECT *"), Ok(("SELECT", " *")));
+        assert!(keyword.parse("* SELECT").is_err());
+        let mut captures = regex_captures("(?i)(a)(b)?").unwrap();
+        assert_eq!(captures.parse("Ac").map(|x| x.0), Ok(vec![Some("A"), Some("A"), None]));
+        assert!(captures.parse("cA").is_err());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_is_anchored() {
+        //A multi line `^` could match after the newline if the search was not anchored
+        let mut word = regex(r"(?m)^\w+").unwrap();
+        assert!(word.parse(" \nabc").is_err());
+        let mut bytes = regex_captures(r"(?-u:\xFF)+").unwrap();
+        assert_eq!(bytes.parse(&b"\xFF\xFFa"[..]), Ok((vec![Some(&b"\xFF\xFF"[..])], &b"a"[..])));
+        assert!(bytes.parse(&b"a\xFF"[..]).is_err());
+    }
+
+    #[test]
+    fn take_while_slice() {
+        let result = take_while(|b: &u8| *b < 3).parse(&[1u8, 2, 3, 4][..]);
+        assert_eq!(result, Ok((&[1u8, 2][..], &[3u8, 4][..])));
+    }
+}
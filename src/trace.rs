@@ -0,0 +1,384 @@
+use std::fmt;
+use primitives::{Parser, ParseError, ParseResult, Positioner, Resumable, State, Stream};
+#[cfg(feature = "trace")]
+use primitives::{Consumed, Error, Info};
+#[cfg(feature = "trace")]
+use std::cell::RefCell;
+
+#[cfg(feature = "trace")]
+struct Tracer {
+    depth: usize,
+    sink: Option<Box<FnMut(&str)>>
+}
+
+#[cfg(feature = "trace")]
+thread_local!(static TRACER: RefCell<Tracer> = RefCell::new(Tracer { depth: 0, sink: None }));
+
+#[cfg(feature = "trace")]
+fn log(line: &str) {
+    TRACER.with(|tracer| {
+        //The sink is taken out of the tracer while it is called so that it is able to run traced
+        //parsers itself, lines traced while the sink is running are discarded
+        let (sink, depth) = {
+            let mut tracer = tracer.borrow_mut();
+            (tracer.sink.take(), tracer.depth)
+        };
+        if let Some(mut sink) = sink {
+            sink(&format!("{:width$}{}", "", line, width = 2 * depth));
+            let mut tracer = tracer.borrow_mut();
+            //Keep the sink if a new one was set while it was running
+            if tracer.sink.is_none() {
+                tracer.sink = Some(sink);
+            }
+        }
+    })
+}
+
+#[cfg(feature = "trace")]
+fn info<T, R>(info: &Info<T, R>) -> String
+    where T: fmt::Debug
+        , R: fmt::Debug {
+    match *info {
+        Info::Token(ref t) => format!("{:?}", t),
+        Info::Range(ref r) => format!("{:?}", r),
+        Info::Owned(ref s) => format!("{:?}", s),
+        Info::Borrowed(s) => format!("{:?}", s)
+    }
+}
+
+#[cfg(feature = "trace")]
+fn errors<T, R>(errors: &[Error<T, R>]) -> String
+    where T: fmt::Debug
+        , R: fmt::Debug {
+    let errors: Vec<_> = errors.iter()
+        .map(|error| match *error {
+            Error::Unexpected(ref i) => format!("unexpected {}", info(i)),
+            Error::Expected(ref i) => format!("expected {}", info(i)),
+            Error::Message(ref i) => info(i),
+            Error::Other(ref err) => err.to_string()
+        })
+        .collect();
+    errors.join(", ")
+}
+
+///Sets the function which receives the lines traced on the current thread, replacing any
+///previous function.
+///Lines are discarded if no function has been set, to print them use for instance
+///`set_tracer(|line| eprintln!("{}", line))`.
+///Does nothing unless the `trace` feature is enabled.
+pub fn set_tracer<F>(sink: F)
+    where F: FnMut(&str) + 'static {
+    #[cfg(feature = "trace")]
+    TRACER.with(|tracer| tracer.borrow_mut().sink = Some(Box::new(sink)));
+    #[cfg(not(feature = "trace"))]
+    drop(sink);
+}
+
+///Calls `f` with `sink` receiving the lines traced on the current thread, restoring the previous
+///function afterwards.
+///Does nothing except calling `f` unless the `trace` feature is enabled.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::trace::with_tracer;
+/// # use std::cell::RefCell;
+/// # use std::rc::Rc;
+/// # fn main() {
+/// let lines = Rc::new(RefCell::new(Vec::new()));
+/// let sink = lines.clone();
+/// let mut parser = many1::<String, _>(digit().debug("digit")).debug("number");
+/// let result = with_tracer(move |line| sink.borrow_mut().push(line.to_string()),
+///                          || parser.parse("1a"));
+/// assert!(result.is_ok());
+/// if cfg!(feature = "trace") {
+///     assert_eq!(*lines.borrow(), [
+///         "number @ line: 1, column: 1",
+///         "  digit @ line: 1, column: 1",
+///         "  digit -> consumed ok @ line: 1, column: 2",
+///         "  digit @ line: 1, column: 2",
+///         "  digit -> empty error @ line: 1, column: 2: unexpected 'a', expected \"digit\"",
+///         "number -> consumed ok @ line: 1, column: 2",
+///     ]);
+/// } else {
+///     assert!(lines.borrow().is_empty());
+/// }
+/// # }
+/// ```
+pub fn with_tracer<F, G, R>(sink: F, f: G) -> R
+    where F: FnMut(&str) + 'static
+        , G: FnOnce() -> R {
+    #[cfg(feature = "trace")]
+    {
+        let _restore = TRACER.with(|tracer| {
+            let mut tracer = tracer.borrow_mut();
+            let previous = ::std::mem::replace(&mut tracer.sink, Some(Box::new(sink)));
+            RestoreTracer { sink: previous, depth: tracer.depth }
+        });
+        f()
+    }
+    #[cfg(not(feature = "trace"))]
+    {
+        drop(sink);
+        f()
+    }
+}
+
+//Restores the previous sink and depth when dropped, even if the traced closure panics
+#[cfg(feature = "trace")]
+struct RestoreTracer {
+    sink: Option<Box<FnMut(&str)>>,
+    depth: usize
+}
+
+#[cfg(feature = "trace")]
+impl Drop for RestoreTracer {
+    fn drop(&mut self) {
+        let sink = self.sink.take();
+        let depth = self.depth;
+        TRACER.with(|tracer| {
+            let mut tracer = tracer.borrow_mut();
+            tracer.sink = sink;
+            tracer.depth = depth;
+        });
+    }
+}
+
+#[derive(Clone)]
+//The name is only used when tracing is enabled
+#[cfg_attr(not(feature = "trace"), allow(dead_code))]
+pub struct Trace<P>(&'static str, P);
+#[cfg(feature = "trace")]
+impl <P> Trace<P> {
+    //Runs `p` through `parse`, tracing the position it starts at and its outcome
+    fn traced<I, F>(&mut self, input: State<I>, parse: F) -> ParseResult<P::Output, I>
+        where I: Stream
+            , I::Item: fmt::Debug
+            , I::Range: fmt::Debug
+            , <I::Item as Positioner>::Position: fmt::Display
+            , P: Parser<Input=I>
+            , F: FnOnce(&mut P, State<I>) -> ParseResult<P::Output, I> {
+        let name = self.0;
+        log(&format!("{} @ {}", name, input.position));
+        TRACER.with(|tracer| tracer.borrow_mut().depth += 1);
+        let before = input.input.clone();
+        let result = parse(&mut self.1, input);
+        TRACER.with(|tracer| tracer.borrow_mut().depth -= 1);
+        match result {
+            Ok((_, Consumed::Consumed(ref rest))) | Ok((_, Consumed::Partial(ref rest))) => {
+                log(&format!("{} -> consumed ok @ {}", name, rest.position));
+            }
+            Ok((_, Consumed::Empty(ref rest))) => {
+                log(&format!("{} -> empty ok @ {}", name, rest.position));
+            }
+            Err(Consumed::Consumed(ref err)) => {
+                log(&format!("{} -> consumed error @ {}: {}", name, err.position, errors(&err.errors)));
+            }
+            Err(Consumed::Partial(ref err)) => {
+                log(&format!("{} -> partial @ {}", name, err.position));
+            }
+            Err(Consumed::Empty(ref err)) if !err.errors.is_empty() => {
+                log(&format!("{} -> empty error @ {}: {}", name, err.position, errors(&err.errors)));
+            }
+            Err(Consumed::Empty(ref err)) => {
+                //Empty errors are usually completed by the caller through `add_error` so add the
+                //same errors to a copy to show the full error
+                let mut full = ParseError::empty(err.position.clone());
+                match before.uncons() {
+                    Ok((t, _)) => full.add_error(Error::Unexpected(Info::Token(t))),
+                    Err(error) => full.add_error(error)
+                }
+                self.1.add_error(&mut full);
+                log(&format!("{} -> empty error @ {}: {}", name, err.position, errors(&full.errors)));
+            }
+        }
+        result
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+impl <P> Trace<P> {
+    #[inline]
+    fn traced<I, F>(&mut self, input: State<I>, parse: F) -> ParseResult<P::Output, I>
+        where I: Stream
+            , P: Parser<Input=I>
+            , F: FnOnce(&mut P, State<I>) -> ParseResult<P::Output, I> {
+        parse(&mut self.1, input)
+    }
+}
+
+//The bounds needed to print the trace are required even if the `trace` feature is disabled so that
+//enabling the feature does not stop any parser from compiling
+impl <I, P> Parser for Trace<P>
+    where I: Stream
+        , I::Item: fmt::Debug
+        , I::Range: fmt::Debug
+        , <I::Item as Positioner>::Position: fmt::Display
+        , P: Parser<Input=I> {
+    type Input = I;
+    type Output = P::Output;
+    fn parse_lazy(&mut self, input: State<I>) -> ParseResult<P::Output, I> {
+        self.traced(input, |p, input| p.parse_lazy(input))
+    }
+    fn add_error(&mut self, errors: &mut ParseError<Self::Input>) {
+        self.1.add_error(errors)
+    }
+}
+
+impl <I, P> Resumable for Trace<P>
+    where I: Stream
+        , I::Item: fmt::Debug
+        , I::Range: fmt::Debug
+        , <I::Item as Positioner>::Position: fmt::Display
+        , P: Resumable<Input=I> {
+    type PartialState = P::PartialState;
+    fn parse_resume(&mut self, input: State<I>, state: &mut P::PartialState) -> ParseResult<P::Output, I> {
+        self.traced(input, |p, input| p.parse_resume(input, state))
+    }
+}
+
+///Wraps `p` so that its name, the position it starts at and its outcome are traced each time it
+///is run, nesting the output of any traced parsers inside `p`.
+///Equivalent to `p.debug(name)`.
+///
+///Tracing is only done if the `trace` feature is enabled, otherwise `trace` just returns a
+///parser which calls `p` directly. In either case the items and ranges of the stream must
+///implement `Debug` and its positions `Display`.
+///
+/// ```
+/// # extern crate combine as pc;
+/// # use pc::*;
+/// # use pc::trace::{set_tracer, trace};
+/// # fn main() {
+/// //Print the trace to stderr, without a tracer the lines are discarded
+/// set_tracer(|line| eprintln!("{}", line));
+/// let mut parser = trace("word", many1::<String, _>(letter()));
+/// assert_eq!(parser.parse("abc").map(|x| x.0), Ok("abc".to_string()));
+/// # }
+/// ```
+pub fn trace<P>(name: &'static str, p: P) -> Trace<P>
+    where P: Parser {
+    Trace(name, p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use primitives::Parser;
+    use combinator::ParserExt;
+    use char::digit;
+
+    fn traced<F>(f: F) -> Vec<String>
+        where F: FnOnce() {
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = lines.clone();
+        with_tracer(move |line| sink.borrow_mut().push(line.to_string()), f);
+        let lines = lines.borrow().clone();
+        lines
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn nested_alternatives() {
+        use combinator::{many, try};
+        use char::string;
+        let lines = traced(|| {
+            let mut parser = many::<Vec<_>, _>(digit()).debug("digits")
+                .skip(trace("alternatives", try(string("ab")).debug("ab").or(string("a").debug("a"))));
+            assert!(parser.parse("1ac").is_ok());
+        });
+        assert_eq!(lines, [
+            "digits @ line: 1, column: 1",
+            "digits -> consumed ok @ line: 1, column: 2",
+            "alternatives @ line: 1, column: 2",
+            "  ab @ line: 1, column: 2",
+            "  ab -> empty error @ line: 1, column: 2: unexpected 'c', expected \"ab\"",
+            "  a @ line: 1, column: 2",
+            "  a -> consumed ok @ line: 1, column: 3",
+            "alternatives -> consumed ok @ line: 1, column: 3",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn consumed_error() {
+        use char::string;
+        let lines = traced(|| {
+            assert!(string("abc").debug("abc").parse("ab").is_err());
+        });
+        assert_eq!(lines, [
+            "abc @ line: 1, column: 1",
+            "abc -> consumed error @ line: 1, column: 1: \"End of input\"",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn sink_can_parse() {
+        use char::letter;
+        let lines = traced(|| {
+            set_tracer(|_| {
+                assert!(letter().debug("inner").parse("a").is_ok());
+            });
+            assert!(digit().debug("digit").parse("1").is_ok());
+        });
+        assert!(lines.is_empty());
+        let lines = traced(|| {
+            assert!(digit().debug("digit").parse("1").is_ok());
+        });
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    #[cfg(not(feature = "trace"))]
+    fn disabled() {
+        let lines = traced(|| {
+            assert!(digit().debug("digit").parse("1").is_ok());
+        });
+        assert!(lines.is_empty());
+    }
+
+    #[test]
+    fn custom_token() {
+        use combinator::satisfy;
+        use primitives::{IndexPosition, Positioner};
+        #[derive(Clone, Debug, PartialEq)]
+        struct Token;
+        impl Positioner for Token {
+            type Position = IndexPosition;
+            fn start() -> IndexPosition {
+                IndexPosition::start()
+            }
+            fn update(&self, position: &mut IndexPosition) {
+                position.index += 1;
+            }
+        }
+        let tokens = [Token];
+        let lines = traced(|| {
+            assert!(satisfy(|_: &Token| true).debug("token").parse(&tokens[..]).is_ok());
+        });
+        assert_eq!(lines.len(), if cfg!(feature = "trace") { 2 } else { 0 });
+    }
+
+    #[test]
+    #[cfg(feature = "trace")]
+    fn tracer_is_restored_after_panic() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        let lines = traced(|| {
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                with_tracer(|_| (), || {
+                    let mut parser = digit().map(|_| -> () { panic!("failed") }).debug("panics");
+                    let _ = parser.parse("1");
+                })
+            }));
+            assert!(result.is_err());
+            assert!(digit().debug("digit").parse("1").is_ok());
+        });
+        assert_eq!(lines, [
+            "digit @ line: 1, column: 1",
+            "digit -> consumed ok @ line: 1, column: 2",
+        ]);
+    }
+}